@@ -0,0 +1,139 @@
+//! 任意の法での畳み込みの実装
+
+use crate::num::{Garner, StaticFp};
+
+use super::FFT;
+
+/// 畳み込みに用いる NTT 素数
+const P1: u64 = 754974721; // 45 * 2^24 + 1
+const P2: u64 = 167772161; // 5 * 2^25 + 1
+const P3: u64 = 469762049; // 7 * 2^26 + 1
+
+/// 3 つの素数の積（復元できる値の上限）
+const P1P2P3: u128 = P1 as u128 * P2 as u128 * P3 as u128;
+
+/// 任意の法 m での線形畳み込みを計算する
+///
+/// 3 つの NTT 素数で畳み込みを計算し，Garner のアルゴリズムで復元する．
+/// 各係数の真の値が 3 つの素数の積（約 5.8 * 10^25）未満である必要がある．
+///
+/// **Arguments**
+/// - `a`, `b`: 配列
+/// - `m`: 法（1 以上）
+pub fn convolve_mod(a: &[u64], b: &[u64], m: u64) -> Result<Vec<u64>, &'static str> {
+    if m == 0 {
+        return Err("`m` should be positive.");
+    }
+
+    let a: Vec<u64> = a.iter().map(|&v| v % m).collect();
+    let b: Vec<u64> = b.iter().map(|&v| v % m).collect();
+
+    Ok(convolve_u128(&a, &b)?
+        .into_iter()
+        .map(|v| (v % m as u128) as u64)
+        .collect())
+}
+
+/// 線形畳み込みを整数として正確に計算する
+///
+/// 3 つの NTT 素数で畳み込みを計算し，Garner のアルゴリズムで復元する．
+/// 各係数の真の値が 3 つの素数の積（約 5.8 * 10^25）以上になり得る場合はエラーを返す．
+pub fn convolve_u128(a: &[u64], b: &[u64]) -> Result<Vec<u128>, &'static str> {
+    if a.is_empty() || b.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // 係数の上限を確認
+    let max_a = *a.iter().max().unwrap() as u128;
+    let max_b = *b.iter().max().unwrap() as u128;
+    let bound = max_a
+        .checked_mul(max_b)
+        .and_then(|v| v.checked_mul(a.len().min(b.len()) as u128));
+    if bound.is_none_or(|v| v >= P1P2P3) {
+        return Err("The coefficients of the convolution may exceed the product of the primes.");
+    }
+
+    let c1 = FFT(StaticFp::<P1>).convolve(&reduce(a, P1), &reduce(b, P1))?;
+    let c2 = FFT(StaticFp::<P2>).convolve(&reduce(a, P2), &reduce(b, P2))?;
+    let c3 = FFT(StaticFp::<P3>).convolve(&reduce(a, P3), &reduce(b, P3))?;
+
+    let garner = Garner::new(&[P1, P2, P3])?;
+
+    Ok(c1
+        .into_iter()
+        .zip(c2)
+        .zip(c3)
+        .map(|((r1, r2), r3)| garner.reconstruct_u128(&[r1, r2, r3]).unwrap())
+        .collect())
+}
+
+/// 各要素を mod p で正規化する
+fn reduce(a: &[u64], p: u64) -> Vec<u64> {
+    a.iter().map(|&v| v % p).collect()
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use super::{convolve_mod, convolve_u128};
+
+    /// 愚直な線形畳み込み
+    fn convolve_naive(a: &[u64], b: &[u64]) -> Vec<u128> {
+        if a.is_empty() || b.is_empty() {
+            return vec![];
+        }
+        let mut res = vec![0; a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                res[i + j] += x as u128 * y as u128;
+            }
+        }
+        res
+    }
+
+    #[rstest(
+        n,
+        m,
+        modulus,
+        case(0, 5, 1000000007),
+        case(1, 1, 1000000007),
+        case(10, 7, 1000000007),
+        case(300, 500, 1000000007),
+        case(1000, 1000, 998244353),
+        case(200, 300, 2147483647),
+        case(50, 50, 1)
+    )]
+    fn test_convolve_mod(n: usize, m: usize, modulus: u64) {
+        let mut rng = rng();
+
+        let a: Vec<u64> = (0..n).map(|_| rng.random_range(0..modulus)).collect();
+        let b: Vec<u64> = (0..m).map(|_| rng.random_range(0..modulus)).collect();
+
+        let expected: Vec<u64> = convolve_naive(&a, &b)
+            .into_iter()
+            .map(|v| (v % modulus as u128) as u64)
+            .collect();
+
+        assert_eq!(convolve_mod(&a, &b, modulus).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_convolve_u128() {
+        let mut rng = rng();
+
+        let a: Vec<u64> = (0..100).map(|_| rng.random_range(0..1 << 40)).collect();
+        let b: Vec<u64> = (0..100).map(|_| rng.random_range(0..1 << 38)).collect();
+
+        assert_eq!(convolve_u128(&a, &b).unwrap(), convolve_naive(&a, &b));
+    }
+
+    #[test]
+    fn test_convolve_error() {
+        assert!(convolve_mod(&[1, 2], &[3, 4], 0).is_err());
+        assert!(convolve_u128(&[u64::MAX], &[u64::MAX]).is_err());
+        assert!(convolve_u128(&[1 << 43; 4], &[1 << 43; 4]).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! Bluestein のアルゴリズム（chirp-z 変換）の実装
+
+use crate::num::{Field, Fp, Modulus};
+
+use super::{convolution::CyclicConvolver, mixed_radix::MixedRadixCore, FFT};
+
+/// 任意の長さの NTT の計画（Bluestein のアルゴリズム）
+///
+/// p - 1 を割り切る任意の n に対して，長さ n の変換を配列の拡張なしに計算する．
+/// jk = T(j + k) - T(j) - T(k)（T(x) = x(x - 1) / 2）を用いて，
+/// 変換を長さ 2 べきの巡回畳み込みに帰着する．
+/// 長さ 2n - 1 以上の 2 べきが 2^k（p = 2^k m + 1）を超えて畳み込みができない場合は，
+/// 混合基数 NTT（[`super::MixedRadix`]）で計算する．
+pub struct Bluestein<M: Modulus = Fp> {
+    /// 畳み込みに用いる FFT
+    fft: FFT<M>,
+    /// 変換の長さ
+    n: usize,
+    /// 変換の本体
+    kind: Kind,
+    /// n^(-1)
+    inv_n: u64,
+}
+
+/// 変換の本体
+enum Kind {
+    /// 巡回畳み込みに帰着する
+    Chirp(Chirp),
+    /// 巡回畳み込みができない場合に混合基数 NTT で計算する
+    MixedRadix(MixedRadixCore),
+}
+
+/// 巡回畳み込みに帰着するための事前計算
+struct Chirp {
+    /// 長さ 2n - 1 以上の 2 べきの巡回畳み込み
+    conv: CyclicConvolver<u64>,
+    /// ω^(-T(j))（j < n）
+    chirp: Vec<u64>,
+    /// ω^(T(j))（j < n）
+    ichirp: Vec<u64>,
+    /// ω^(T(m))（m < 2n - 1）を畳み込み用に変換したもの
+    kernel: Vec<u64>,
+    /// ω^(-T(m))（m < 2n - 1）を畳み込み用に変換したもの
+    ikernel: Vec<u64>,
+}
+
+impl<M: Modulus> Bluestein<M> {
+    /// 長さ n の変換の計画を作成する
+    ///
+    /// **Arguments**
+    /// - `field`: 変換を行う体
+    /// - `n`: 変換の長さ（p - 1 の約数）
+    pub fn new(field: M, n: usize) -> Result<Self, &'static str> {
+        let w = field.to_repr(field.root_of_unity(n as u64)?);
+        let winv = field.inv_repr(w);
+
+        let inv_n = field.inv_repr(field.embed(n as u64));
+
+        let fft = FFT(field);
+        let kind = match fft.cyclic_convolver((2 * n - 1).next_power_of_two()) {
+            Ok(conv) => {
+                let chirp = fft.chirp(n, n, winv);
+                let ichirp = fft.chirp(n, n, w);
+
+                // 畳み込みの相手をあらかじめ変換しておく
+                let kernel = conv.kernel(&fft, &fft.chirp(2 * n - 1, n, w));
+                let ikernel = conv.kernel(&fft, &fft.chirp(2 * n - 1, n, winv));
+
+                Kind::Chirp(Chirp {
+                    conv,
+                    chirp,
+                    ichirp,
+                    kernel,
+                    ikernel,
+                })
+            }
+            Err(_) => Kind::MixedRadix(MixedRadixCore::new(&fft, n)?),
+        };
+
+        Ok(Self {
+            fft,
+            n,
+            kind,
+            inv_n,
+        })
+    }
+
+    /// 変換の長さ
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// 変換を行う体
+    pub fn field(&self) -> &M {
+        &self.fft.0
+    }
+
+    /// 配列をその場でフーリエ変換する
+    pub fn forward(&self, X: &mut [u64]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.forward_repr(X);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 配列をその場でフーリエ逆変換する
+    pub fn inverse(&self, X: &mut [u64]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.inverse_repr(X);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 内部表現の配列をその場でフーリエ変換する
+    pub(crate) fn forward_repr(&self, X: &mut [u64]) {
+        match &self.kind {
+            Kind::Chirp(c) => self.transform(X, &c.conv, &c.chirp, &c.kernel),
+            Kind::MixedRadix(core) => core.apply(&self.fft, X, false),
+        }
+    }
+
+    /// 内部表現の配列をその場でフーリエ逆変換する
+    pub(crate) fn inverse_repr(&self, X: &mut [u64]) {
+        match &self.kind {
+            Kind::Chirp(c) => self.transform(X, &c.conv, &c.ichirp, &c.ikernel),
+            Kind::MixedRadix(core) => core.apply(&self.fft, X, true),
+        }
+
+        let field = self.field();
+        X.iter_mut()
+            .for_each(|v| *v = field.mul_repr(*v, self.inv_n));
+    }
+
+    /// X_k = c_k Σ_j (X_j c_j) c'_{j+k} を計算する
+    ///
+    /// - `conv`: 長さ 2n - 1 以上の 2 べきの巡回畳み込み
+    /// - `chirp`: c_j
+    /// - `kernel`: c'_m を畳み込み用に変換したもの
+    fn transform(&self, X: &mut [u64], conv: &CyclicConvolver<u64>, chirp: &[u64], kernel: &[u64]) {
+        let field = self.field();
+        let n = self.n;
+
+        // 相関を畳み込みにするため反転して並べる
+        let mut a = vec![0; conv.len()];
+        for (j, (&x, &c)) in X.iter().zip(chirp).enumerate() {
+            a[n - 1 - j] = field.mul_repr(x, c);
+        }
+        conv.apply(&self.fft, &mut a, kernel);
+
+        for (k, (x, &c)) in X.iter_mut().zip(chirp).enumerate() {
+            *x = field.mul_repr(a[n - 1 + k], c);
+        }
+    }
+
+    /// 配列の長さが計画と一致するか確認する
+    fn check_len(&self, X: &[u64]) -> Result<(), &'static str> {
+        if X.len() != self.n {
+            return Err("The length of the array does not match the plan.");
+        }
+        Ok(())
+    }
+}
+
+impl<M: Modulus> FFT<M> {
+    /// w^(T(j))（j < len，T(j) = j(j - 1) / 2）を内部表現で求める
+    ///
+    /// - `n`: w の位数
+    fn chirp(&self, len: usize, n: usize, w: u64) -> Vec<u64> {
+        // T(j + 1) = T(j) + j を n で割った余りで管理する
+        let mut t = 0;
+        (0..len)
+            .map(|j| {
+                let res = self.0.pow_repr(w, t as u64);
+                t = (t + j) % n;
+                res
+            })
+            .collect()
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::{
+        ntt::{dft::dft_naive, DFT},
+        num::{Fp, StaticFp},
+    };
+
+    use super::Bluestein;
+
+    #[rstest(
+        n,
+        p,
+        case(1, 12289),
+        case(3, 12289),
+        case(6, 12289),
+        case(96, 12289),
+        case(7, 998244353),
+        case(17, 998244353),
+        case(119, 998244353),
+        case(224, 998244353),
+        case(11, 5767169),
+        case(176, 5767169),
+        case(255, 18446744069414584321),
+        // 長さ 2n - 1 以上の 2 べきの畳み込みができず，混合基数 NTT で計算する
+        case(3072, 12289),
+        case(83, 167)
+    )]
+    fn test_bluestein(n: usize, p: u64) {
+        let mut rng = rng();
+        let fp = Fp::new(p).unwrap();
+        let plan = Bluestein::new(&fp, n).unwrap();
+        assert_eq!(plan.size(), n);
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+
+        let mut X = arr.clone();
+        plan.forward(&mut X).unwrap();
+        assert_eq!(X, dft_naive(&fp, &arr));
+
+        plan.inverse(&mut X).unwrap();
+        assert_eq!(X, arr);
+    }
+
+    #[rstest(n, case(1), case(2), case(16), case(512))]
+    fn test_bluestein_pow2(n: usize) {
+        let mut rng = rng();
+        let plan = Bluestein::new(StaticFp::<998244353>, n).unwrap();
+        let dft = DFT(StaticFp::<998244353>);
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..998244353)).collect();
+
+        let mut X = arr.clone();
+        plan.forward(&mut X).unwrap();
+        assert_eq!(X, dft.dft(&arr).unwrap());
+
+        plan.inverse(&mut X).unwrap();
+        assert_eq!(X, arr);
+    }
+
+    #[test]
+    fn test_bluestein_error() {
+        let fp = Fp::new(12289).unwrap();
+
+        assert!(Bluestein::new(&fp, 0).is_err());
+        assert_eq!(
+            Bluestein::new(&fp, 5).err(),
+            Some("The order of the root of unity should divide p - 1.")
+        );
+        let plan = Bluestein::new(&fp, 3).unwrap();
+        assert!(plan.forward(&mut [1, 2]).is_err());
+        assert!(plan.inverse(&mut [1, 2, 3, 4]).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! 畳み込みの実装
+
+use crate::num::Field;
+
+use super::FFT;
+
+/// 畳み込みの長さが体の対応する変換長を超えた場合のエラー
+const ERR_TOO_LONG: &str =
+    "The length of the convolution exceeds the maximum transform length 2^k of the field (not enough factors of 2 in p - 1).";
+
+impl<F: Field> FFT<F> {
+    /// 線形畳み込みを計算する
+    ///
+    /// 長さ `a.len() + b.len() - 1` の配列 c_k = Σ_{i+j=k} a_i b_j を返す．
+    /// 切り詰めたフーリエ変換を用いるため，計算量は長さに対してなめらかに増加する．
+    pub fn convolve(&self, a: &[F::Elem], b: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        if a.is_empty() || b.is_empty() {
+            return Ok(vec![]);
+        }
+        let len = a.len() + b.len() - 1;
+
+        let n = len.next_power_of_two();
+        if n.trailing_zeros() as usize > self.0.max_pow2() {
+            return Err(ERR_TOO_LONG);
+        }
+
+        // 長さ len の TFT を用いて，必要な点でだけ評価・補間する
+        let (pw, ipw) = self.tft_tables(len)?;
+        let transform = |X: &[F::Elem]| {
+            let mut res: Vec<F::Elem> = X.iter().map(|&v| self.0.to_repr(v)).collect();
+            res.resize(n, self.0.zero());
+            self.tft_core(&mut res, X.len(), len, &pw, 1);
+            res
+        };
+        let mut fa = transform(a);
+        let fb = transform(b);
+
+        for (x, &y) in fa[..len].iter_mut().zip(&fb[..len]) {
+            *x = self.0.mul_repr(*x, y);
+        }
+        fa[len..].fill(self.0.zero());
+
+        self.itft_core(&mut fa, len, &pw, &ipw, 1);
+        fa.truncate(len);
+
+        Ok(fa.into_iter().map(|v| self.0.to_value(v)).collect())
+    }
+
+    /// 巡回畳み込みを計算する
+    ///
+    /// 長さ n の配列 c_k = Σ_{i+j≡k (mod n)} a_i b_j を返す．
+    /// n が 2 べきでない場合は線形畳み込みを経由して計算する．
+    pub fn cyclic_convolve(
+        &self,
+        a: &[F::Elem],
+        b: &[F::Elem],
+    ) -> Result<Vec<F::Elem>, &'static str> {
+        let n = Self::check_equal_len(a, b)?;
+        if n == 0 {
+            return Ok(vec![]);
+        }
+
+        if n.is_power_of_two() {
+            let a: Vec<F::Elem> = a.iter().map(|&v| self.0.to_repr(v)).collect();
+            let b: Vec<F::Elem> = b.iter().map(|&v| self.0.to_repr(v)).collect();
+
+            let res = self.cyclic_convolve_repr(&a, &b, n)?;
+
+            return Ok(res.into_iter().map(|v| self.0.to_value(v)).collect());
+        }
+
+        // 線形畳み込みを折り返す
+        let linear = self.convolve(a, b)?;
+        let (lo, hi) = linear.split_at(n);
+        let mut res = lo.to_vec();
+        for (r, &v) in res.iter_mut().zip(hi) {
+            *r = self.add_value(*r, v);
+        }
+
+        Ok(res)
+    }
+
+    /// 負巡回畳み込みを計算する
+    ///
+    /// 長さ n の配列 c_k = Σ_{i+j=k} a_i b_j - Σ_{i+j=k+n} a_i b_j を返す．
+    /// （x^n + 1 を法とする多項式の積）
+    pub fn negacyclic_convolve(
+        &self,
+        a: &[F::Elem],
+        b: &[F::Elem],
+    ) -> Result<Vec<F::Elem>, &'static str> {
+        let n = Self::check_equal_len(a, b)?;
+        if n == 0 {
+            return Ok(vec![]);
+        }
+
+        if n.is_power_of_two() {
+            // ψ^n = -1 となる ψ（1 の原始 2n 乗根）でひねる
+            let log_n = n.trailing_zeros() as usize;
+            if log_n + 1 > self.0.max_pow2() {
+                return Err(ERR_TOO_LONG);
+            }
+            let psi = self.0.root_pow2(log_n + 1)?;
+            let psi_inv = self.0.inv_repr(psi);
+
+            let twist = |X: &[F::Elem], psi: F::Elem| -> Vec<F::Elem> {
+                let mut x = self.0.one();
+                X.iter()
+                    .map(|&v| {
+                        let res = self.0.mul_repr(self.0.to_repr(v), x);
+                        x = self.0.mul_repr(x, psi);
+                        res
+                    })
+                    .collect()
+            };
+
+            let res = self.cyclic_convolve_repr(&twist(a, psi), &twist(b, psi), n)?;
+
+            let mut x = self.0.one();
+            return Ok(res
+                .into_iter()
+                .map(|v| {
+                    let res = self.0.to_value(self.0.mul_repr(v, x));
+                    x = self.0.mul_repr(x, psi_inv);
+                    res
+                })
+                .collect());
+        }
+
+        // 線形畳み込みを符号を反転して折り返す
+        let linear = self.convolve(a, b)?;
+        let (lo, hi) = linear.split_at(n);
+        let mut res = lo.to_vec();
+        for (r, &v) in res.iter_mut().zip(hi) {
+            *r = self.sub_value(*r, v);
+        }
+
+        Ok(res)
+    }
+
+    /// 内部表現の配列の長さ n の巡回畳み込みを計算する
+    ///
+    /// - `n`: 2 べき（`a`, `b` の長さは n 以下）
+    pub(crate) fn cyclic_convolve_repr(
+        &self,
+        a: &[F::Elem],
+        b: &[F::Elem],
+        n: usize,
+    ) -> Result<Vec<F::Elem>, &'static str> {
+        let conv = self.cyclic_convolver(n)?;
+        let kernel = conv.kernel(self, b);
+
+        let mut res = a.to_vec();
+        res.resize(n, self.0.zero());
+        conv.apply(self, &mut res, &kernel);
+
+        Ok(res)
+    }
+
+    /// 長さ n の巡回畳み込みを繰り返し行うための事前計算をする
+    ///
+    /// - `n`: 2 べき
+    pub(crate) fn cyclic_convolver(
+        &self,
+        n: usize,
+    ) -> Result<CyclicConvolver<F::Elem>, &'static str> {
+        let log_n = n.trailing_zeros() as usize;
+        if log_n > self.0.max_pow2() {
+            return Err(ERR_TOO_LONG);
+        }
+
+        let w = self.0.root_pow2(log_n)?;
+
+        Ok(CyclicConvolver {
+            len: n,
+            twiddles: self.twiddles(n, w),
+            itwiddles: self.twiddles(n, self.0.inv_repr(w)),
+            inv_len: self.0.inv_repr(self.0.embed(n as u64)),
+        })
+    }
+
+    /// 通常の表現での a + b
+    fn add_value(&self, a: F::Elem, b: F::Elem) -> F::Elem {
+        let (a, b) = (self.0.to_repr(a), self.0.to_repr(b));
+        self.0.to_value(self.0.add_repr(a, b))
+    }
+
+    /// 通常の表現での a - b
+    fn sub_value(&self, a: F::Elem, b: F::Elem) -> F::Elem {
+        let (a, b) = (self.0.to_repr(a), self.0.to_repr(b));
+        self.0.to_value(self.0.sub_repr(a, b))
+    }
+
+    /// 2 つの配列の長さが等しいか確認する
+    fn check_equal_len(a: &[F::Elem], b: &[F::Elem]) -> Result<usize, &'static str> {
+        if a.len() != b.len() {
+            return Err("The lengths of the arrays should be equal.");
+        }
+        Ok(a.len())
+    }
+}
+
+/// 長さ 2 べきの巡回畳み込みの事前計算
+///
+/// 回転因子の表を保持し，片方を固定した畳み込みを繰り返し行う．
+/// 入出力はすべて内部表現で与える．
+pub(crate) struct CyclicConvolver<E> {
+    /// 畳み込みの長さ（2 べき）
+    len: usize,
+    /// 回転因子の表
+    twiddles: Vec<E>,
+    /// 逆変換の回転因子の表
+    itwiddles: Vec<E>,
+    /// len^(-1)
+    inv_len: E,
+}
+
+impl<E: Copy> CyclicConvolver<E> {
+    /// 畳み込みの長さ
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 固定する配列（長さ len 以下）をフーリエ変換し，len^(-1) 倍したものを求める
+    ///
+    /// 結果はビット反転順のまま [`CyclicConvolver::apply`] に与える．
+    pub(crate) fn kernel<F: Field<Elem = E>>(&self, fft: &FFT<F>, b: &[E]) -> Vec<E> {
+        let mut res = b.to_vec();
+        res.resize(self.len, fft.0.zero());
+
+        fft.fft_core(&mut res, &self.twiddles);
+        res.iter_mut()
+            .for_each(|v| *v = fft.0.mul_repr(*v, self.inv_len));
+
+        res
+    }
+
+    /// 長さ len の配列 `a` を `a` と固定した配列との巡回畳み込みで置き換える
+    ///
+    /// - `kernel`: [`CyclicConvolver::kernel`] の結果
+    pub(crate) fn apply<F: Field<Elem = E>>(&self, fft: &FFT<F>, a: &mut [E], kernel: &[E]) {
+        debug_assert_eq!(a.len(), self.len);
+
+        // ビット反転順のまま各点積をとり，逆変換する
+        fft.fft_core(a, &self.twiddles);
+        for (x, &y) in a.iter_mut().zip(kernel) {
+            *x = fft.0.mul_repr(*x, y);
+        }
+        fft.ifft_core(a, &self.itwiddles);
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::num::{Fp, StaticFp};
+
+    use super::{super::FFT, ERR_TOO_LONG};
+
+    /// 愚直な線形畳み込み
+    fn convolve_naive(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+        if a.is_empty() || b.is_empty() {
+            return vec![];
+        }
+        let mut res = vec![0; a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                res[i + j] = ((res[i + j] as u128 + x as u128 * y as u128) % p as u128) as u64;
+            }
+        }
+        res
+    }
+
+    #[rstest(
+        n,
+        m,
+        p,
+        case(0, 3, 998244353),
+        case(1, 1, 998244353),
+        case(3, 5, 17),
+        case(7, 9, 5767169),
+        case(100, 37, 998244353),
+        case(1000, 1000, 998244353),
+        case(513, 256, 4179340454199820289),
+        case(1024, 1, 998244353),
+        case(1024, 2, 998244353),
+        case(700, 600, 5767169)
+    )]
+    fn test_convolve(n: usize, m: usize, p: u64) {
+        let mut rng = rng();
+
+        let a: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+        let b: Vec<u64> = (0..m).map(|_| rng.random_range(0..p)).collect();
+
+        let fft = FFT(Fp::new(p).unwrap());
+
+        assert_eq!(fft.convolve(&a, &b).unwrap(), convolve_naive(&a, &b, p));
+    }
+
+    #[rstest(n, case(1), case(4), case(6), case(16), case(100))]
+    fn test_cyclic_negacyclic(n: usize) {
+        const P: u64 = 998244353;
+        let mut rng = rng();
+
+        let a: Vec<u64> = (0..n).map(|_| rng.random_range(0..P)).collect();
+        let b: Vec<u64> = (0..n).map(|_| rng.random_range(0..P)).collect();
+        let linear = convolve_naive(&a, &b, P);
+
+        let mut cyclic = linear[..n].to_vec();
+        let mut negacyclic = linear[..n].to_vec();
+        for (i, &v) in linear.iter().enumerate().skip(n) {
+            cyclic[i - n] = (cyclic[i - n] + v) % P;
+            negacyclic[i - n] = (negacyclic[i - n] + P - v) % P;
+        }
+
+        let fft = FFT(StaticFp::<P>);
+
+        assert_eq!(fft.cyclic_convolve(&a, &b).unwrap(), cyclic);
+        assert_eq!(fft.negacyclic_convolve(&a, &b).unwrap(), negacyclic);
+    }
+
+    #[test]
+    fn test_convolve_error() {
+        // 17 = 2^4 + 1 より，長さ 16 までの変換しかできない
+        let fft = FFT(StaticFp::<17>);
+
+        assert!(fft.convolve(&[1; 8], &[1; 9]).is_ok());
+        assert_eq!(fft.convolve(&[1; 8], &[1; 10]), Err(ERR_TOO_LONG));
+        assert!(fft.cyclic_convolve(&[1; 16], &[1; 16]).is_ok());
+        assert_eq!(
+            fft.negacyclic_convolve(&[1; 16], &[1; 16]),
+            Err(ERR_TOO_LONG)
+        );
+        assert!(fft.cyclic_convolve(&[1; 3], &[1; 4]).is_err());
+    }
+}
@@ -1,49 +1,68 @@
 //! 離散フーリエ変換の実装
 
-use crate::num::Fp;
+use crate::num::{Field, Fp, Modint, Modulus};
 
 /// 離散フーリエ変換の実装
-pub struct DFT(pub Fp);
+pub struct DFT<F: Field = Fp>(pub F);
 
-impl DFT {
+impl<F: Field> DFT<F> {
     /// 入力された配列をフーリエ変換する
-    pub fn dft(&self, X: &[u64]) -> Result<Vec<u64>, &'static str> {
+    pub fn dft(&self, X: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        // 内部表現に変換して計算
+        let X: Vec<F::Elem> = X.iter().map(|&v| self.0.to_repr(v)).collect();
+        let res = self.dft_repr(&X)?;
+
+        Ok(res.into_iter().map(|v| self.0.to_value(v)).collect())
+    }
+
+    /// 入力された配列をフーリエ逆変換する
+    pub fn idft(&self, F: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        // 内部表現に変換して計算
+        let F: Vec<F::Elem> = F.iter().map(|&v| self.0.to_repr(v)).collect();
+        let res = self.idft_repr(&F)?;
+
+        Ok(res.into_iter().map(|v| self.0.to_value(v)).collect())
+    }
+
+    /// 内部表現の配列をフーリエ変換する
+    fn dft_repr(&self, X: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
         let (i, X) = self.extend_array(X)?;
-        let w = self.0.root_pow2m(i)?;
+        let w = self.0.root_pow2(i)?;
 
         Ok(self.dft_core(X, w))
     }
 
-    /// 入力された配列をフーリエ逆変換する
-    pub fn idft(&self, F: &[u64]) -> Result<Vec<u64>, &'static str> {
+    /// 内部表現の配列をフーリエ逆変換する
+    fn idft_repr(&self, F: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
         let (i, F) = self.extend_array(F)?;
-        let w = self.0.root_pow2m(i)?;
-        let winv = self.0.inv(w);
+        let w = self.0.root_pow2(i)?;
+        let winv = self.0.inv_repr(w);
 
         let mut res = self.dft_core(F, winv);
         let n = res.len();
 
         // 逆変換後の配列を正規化
-        let inv_n = self.0.inv(n as u64);
-        res.iter_mut().for_each(|v| *v = self.0.mul(*v, inv_n));
+        let inv_n = self.0.inv_repr(self.0.embed(n as u64));
+        res.iter_mut().for_each(|v| *v = self.0.mul_repr(*v, inv_n));
 
         Ok(res)
     }
 
     /// フーリエ変換，フーリエ逆変換の共通部分
     ///
+    /// 入出力は内部表現で与える．
+    ///
     /// - `w`: 回転演算子
-    fn dft_core(&self, X: Vec<u64>, w: u64) -> Vec<u64> {
+    fn dft_core(&self, X: Vec<F::Elem>, w: F::Elem) -> Vec<F::Elem> {
         let n = X.len();
-
         (0..n)
             .map(|i| {
                 (0..n)
                     .map(|j| {
-                        let rot = self.0.pow(w, i * j);
-                        self.0.mul(X[j], rot)
+                        let rot = self.0.pow_repr(w, (i * j) as u64);
+                        self.0.mul_repr(X[j], rot)
                     })
-                    .fold(0, |acc, v| self.0.add(acc, v))
+                    .fold(self.0.zero(), |acc, v| self.0.add_repr(acc, v))
             })
             .collect()
     }
@@ -55,7 +74,7 @@ impl DFT {
     ///
     /// **Returns**
     /// - `(i, res)`: 配列の長さを 2^i に拡張した結果
-    fn extend_array(&self, array: &[u64]) -> Result<(usize, Vec<u64>), &'static str> {
+    fn extend_array(&self, array: &[F::Elem]) -> Result<(usize, Vec<F::Elem>), &'static str> {
         let n = array.len();
         // 2^i >= n となるような最小の i
         let mut i = 0;
@@ -64,25 +83,70 @@ impl DFT {
             i += 1;
             n_ *= 2;
         }
-        if i > self.0.k {
+        if i > self.0.max_pow2() {
             return Err("The prime p does not have enough factors of 2 in (p - 1).");
         }
         // 配列を生成
         let mut res = array.to_vec();
         // 残りをゼロ埋め
-        res.extend(std::iter::repeat_n(0, n_ - n));
+        res.extend(std::iter::repeat_n(self.0.zero(), n_ - n));
 
         Ok((i, res))
     }
 }
 
+impl<M: Modulus + Copy> DFT<M> {
+    /// 有限体の元の配列をフーリエ変換する
+    pub fn dft_modint(&self, X: &[Modint<M>]) -> Result<Vec<Modint<M>>, &'static str> {
+        let X: Vec<u64> = X.iter().map(|x| self.raw(x)).collect();
+        let res = self.dft_repr(&X)?;
+
+        Ok(res
+            .into_iter()
+            .map(|v| Modint::from_raw(self.0, v))
+            .collect())
+    }
+
+    /// 有限体の元の配列をフーリエ逆変換する
+    pub fn idft_modint(&self, F: &[Modint<M>]) -> Result<Vec<Modint<M>>, &'static str> {
+        let F: Vec<u64> = F.iter().map(|x| self.raw(x)).collect();
+        let res = self.idft_repr(&F)?;
+
+        Ok(res
+            .into_iter()
+            .map(|v| Modint::from_raw(self.0, v))
+            .collect())
+    }
+
+    /// 元の Montgomery 表現を取り出す
+    fn raw(&self, x: &Modint<M>) -> u64 {
+        debug_assert_eq!(x.modulus().modulus(), self.0.modulus());
+        x.raw()
+    }
+}
+
+/// 長さ n の離散フーリエ変換を定義どおりに計算する（2 べきでない長さの変換のテスト用）
+#[cfg(test)]
+pub(crate) fn dft_naive(fp: &Fp, X: &[u64]) -> Vec<u64> {
+    let n = X.len();
+    let pw = fp.root_of_unity_powers(n as u64).unwrap();
+
+    (0..n)
+        .map(|k| {
+            X.iter()
+                .enumerate()
+                .fold(0, |acc, (j, &x)| fp.add(acc, fp.mul(x, pw[j * k % n])))
+        })
+        .collect()
+}
+
 // ===== テスト =====
 #[cfg(test)]
 mod test {
     use rand::{rng, Rng};
     use rstest::rstest;
 
-    use crate::num::Fp;
+    use crate::num::{Fp, Modint, StaticFp};
 
     use super::DFT;
 
@@ -156,7 +220,7 @@ mod test {
 
         {
             let arr = vec![
-                31415926, 53589793, 23846264, 33832795, 02884197, 16939937, 51058209, 74944592,
+                31415926, 53589793, 23846264, 33832795, 2884197, 16939937, 51058209, 74944592,
             ];
 
             let fp = Fp::new(998244353).unwrap();
@@ -175,6 +239,55 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_dft_static() {
+        let arr = vec![31415, 92653, 58979, 32384, 62643, 38327, 95028];
+
+        let dft_dyn = DFT(Fp::new(998244353).unwrap());
+        let dft_static = DFT(StaticFp::<998244353>);
+
+        let res = dft_static.dft(&arr).unwrap();
+        assert_eq!(res, dft_dyn.dft(&arr).unwrap());
+
+        let res2 = dft_static.idft(&res).unwrap();
+        assert_eq!(res2, dft_dyn.idft(&res).unwrap());
+        assert_eq!(&res2[..arr.len()], arr);
+    }
+
+    #[test]
+    fn test_dft_modint() {
+        let arr = vec![31415, 92653, 58979, 32384, 62643, 38327, 95028];
+
+        {
+            let dft = DFT(StaticFp::<998244353>);
+            let X: Vec<Modint<_>> = arr.iter().map(|&x| Modint::from(x)).collect();
+
+            let res = dft.dft_modint(&X).unwrap();
+            assert_eq!(
+                res.iter().map(|x| x.value()).collect::<Vec<_>>(),
+                dft.dft(&arr).unwrap()
+            );
+
+            let res2 = dft.idft_modint(&res).unwrap();
+            assert_eq!(&res2[..X.len()], X);
+        }
+
+        {
+            let fp = Fp::new(5767169).unwrap();
+            let dft = DFT(&fp);
+            let X: Vec<Modint<_>> = arr.iter().map(|&x| Modint::new(&fp, x)).collect();
+
+            let res = dft.dft_modint(&X).unwrap();
+            assert_eq!(
+                res.iter().map(|x| x.value()).collect::<Vec<_>>(),
+                dft.dft(&arr).unwrap()
+            );
+
+            let res2 = dft.idft_modint(&res).unwrap();
+            assert_eq!(&res2[..X.len()], X);
+        }
+    }
+
     #[rstest(
         size,
         p,
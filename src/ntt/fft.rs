@@ -1,70 +1,251 @@
 //! 高速フーリエ変換の実装
 
-use crate::num::Fp;
+use crate::num::{Field, Fp, Modint, Modulus};
 
 /// 高速フーリエ変換の実装
-pub struct FFT(pub Fp);
+pub struct FFT<F: Field = Fp>(pub F);
 
-impl FFT {
+impl<F: Field> FFT<F> {
     /// 入力された配列をフーリエ変換する
-    pub fn fft(&self, X: &[u64]) -> Result<Vec<u64>, &'static str> {
-        let (i, X) = self.extend_array(X)?;
-        let w = self.0.root_pow2m(i)?;
+    pub fn fft(&self, X: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        // 内部表現に変換して計算
+        let X: Vec<F::Elem> = X.iter().map(|&v| self.0.to_repr(v)).collect();
+        let res = self.fft_repr(&X)?;
 
-        Ok(self.fft_core(X, w))
+        Ok(res.into_iter().map(|v| self.0.to_value(v)).collect())
     }
 
     /// 入力された配列をフーリエ逆変換する
-    pub fn ifft(&self, F: &[u64]) -> Result<Vec<u64>, &'static str> {
-        let (i, F) = self.extend_array(F)?;
-        let w = self.0.root_pow2m(i)?;
-        let winv = self.0.inv(w);
+    pub fn ifft(&self, F: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        // 内部表現に変換して計算
+        let F: Vec<F::Elem> = F.iter().map(|&v| self.0.to_repr(v)).collect();
+        let res = self.ifft_repr(&F)?;
 
-        let mut res = self.fft_core(F, winv);
-        let n = res.len();
+        Ok(res.into_iter().map(|v| self.0.to_value(v)).collect())
+    }
+
+    /// 長さが 2 べきの配列をその場でフーリエ変換する
+    ///
+    /// 配列の拡張は行わず，長さが 2 べきでない場合はエラーを返す．
+    ///
+    /// **Arguments**
+    /// - `X`: 配列
+    /// - `scratch`: 回転因子の表を格納する作業領域（長さ n/2 以上）．
+    ///   `None` の場合は回転因子を逐次計算する．
+    pub fn fft_inplace(
+        &self,
+        X: &mut [F::Elem],
+        scratch: Option<&mut [F::Elem]>,
+    ) -> Result<(), &'static str> {
+        let w = self.0.root_pow2(self.check_len(X)?)?;
+        // 作業領域の長さを確認してから配列を変更する
+        let pw = scratch
+            .map(|pw| self.fill_twiddles(pw, X.len(), w))
+            .transpose()?;
+
+        X.iter_mut().for_each(|v| *v = self.0.to_repr(*v));
+        match pw {
+            Some(pw) => self.fft_core(X, pw),
+            None => self.fft_core_direct(X, w),
+        }
+        bit_reverse(X);
+        X.iter_mut().for_each(|v| *v = self.0.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 長さが 2 べきの配列をその場でフーリエ逆変換する
+    ///
+    /// 配列の拡張は行わず，長さが 2 べきでない場合はエラーを返す．
+    ///
+    /// **Arguments**
+    /// - `F`: 配列
+    /// - `scratch`: 回転因子の表を格納する作業領域（長さ n/2 以上）．
+    ///   `None` の場合は回転因子を逐次計算する．
+    pub fn ifft_inplace(
+        &self,
+        F: &mut [F::Elem],
+        scratch: Option<&mut [F::Elem]>,
+    ) -> Result<(), &'static str> {
+        let w = self.0.root_pow2(self.check_len(F)?)?;
+        let winv = self.0.inv_repr(w);
+        // 作業領域の長さを確認してから配列を変更する
+        let pw = scratch
+            .map(|pw| self.fill_twiddles(pw, F.len(), winv))
+            .transpose()?;
+
+        F.iter_mut().for_each(|v| *v = self.0.to_repr(*v));
+        bit_reverse(F);
+        match pw {
+            Some(pw) => self.ifft_core(F, pw),
+            None => self.ifft_core_direct(F, winv),
+        }
+
+        // 逆変換後の配列を正規化
+        let inv_n = self.0.inv_repr(self.0.embed(F.len() as u64));
+        F.iter_mut()
+            .for_each(|v| *v = self.0.to_value(self.0.mul_repr(*v, inv_n)));
+
+        Ok(())
+    }
+
+    /// 内部表現の配列をフーリエ変換する
+    fn fft_repr(&self, X: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        let (i, mut X) = self.extend_array(X)?;
+        let w = self.0.root_pow2(i)?;
+        let pw = self.twiddles(X.len(), w);
+
+        self.fft_core(&mut X, &pw);
+        bit_reverse(&mut X);
+
+        Ok(X)
+    }
+
+    /// 内部表現の配列をフーリエ逆変換する
+    fn ifft_repr(&self, F: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        let (i, mut F) = self.extend_array(F)?;
+        let w = self.0.root_pow2(i)?;
+        let winv = self.0.inv_repr(w);
+
+        let n = F.len();
+        let pw = self.twiddles(n, winv);
+
+        bit_reverse(&mut F);
+        self.ifft_core(&mut F, &pw);
 
         // 逆変換後の配列を正規化
-        let inv_n = self.0.inv(n as u64);
-        res.iter_mut().for_each(|v| *v = self.0.mul(*v, inv_n));
+        let inv_n = self.0.inv_repr(self.0.embed(n as u64));
+        F.iter_mut().for_each(|v| *v = self.0.mul_repr(*v, inv_n));
+
+        Ok(F)
+    }
+
+    /// フーリエ変換の本体（Gentleman–Sande 型，周波数間引き）
+    ///
+    /// 入出力は内部表現で与え，結果はビット反転順で格納される．
+    ///
+    /// - `pw`: 回転因子の表（[`FFT::twiddles`] の結果）
+    pub(crate) fn fft_core(&self, X: &mut [F::Elem], pw: &[F::Elem]) {
+        self.fft_core_step(X, pw, 1);
+    }
 
-        Ok(res)
+    /// 長さ n * step の変換の回転因子の表を用いて [`FFT::fft_core`] を計算する
+    ///
+    /// - `step`: 回転因子の表を参照する間隔
+    pub(crate) fn fft_core_step(&self, X: &mut [F::Elem], pw: &[F::Elem], step: usize) {
+        self.0.dif_core(X, pw, step);
+    }
+
+    /// フーリエ逆変換の本体（Cooley–Tukey 型，時間間引き）
+    ///
+    /// 入力はビット反転順で与え，結果は通常の順序で格納される．
+    ///
+    /// - `pw`: 回転因子の表（[`FFT::twiddles`] の結果）
+    pub(crate) fn ifft_core(&self, X: &mut [F::Elem], pw: &[F::Elem]) {
+        self.ifft_core_step(X, pw, 1);
+    }
+
+    /// 長さ n * step の変換の回転因子の表を用いて [`FFT::ifft_core`] を計算する
+    ///
+    /// - `step`: 回転因子の表を参照する間隔
+    pub(crate) fn ifft_core_step(&self, X: &mut [F::Elem], pw: &[F::Elem], step: usize) {
+        self.0.dit_core(X, pw, step);
     }
 
-    /// フーリエ変換，フーリエ逆変換の共通部分
+    /// フーリエ変換の本体を回転因子の表を用いずに計算する
     ///
-    /// - `w`: 回転演算子
-    fn fft_core(&self, X: Vec<u64>, w: u64) -> Vec<u64> {
+    /// [`FFT::fft_core`] と同じ結果を，メモリを確保せずに求める．
+    fn fft_core_direct(&self, X: &mut [F::Elem], w: F::Elem) {
         let n = X.len();
 
-        if n == 1 {
-            return X.to_vec();
+        let mut h = n / 2;
+        // 1 の原始 2h 乗根
+        let mut wm = w;
+        while h >= 1 {
+            let mut tw = self.0.one();
+            for j in 0..h {
+                for s in (j..n).step_by(2 * h) {
+                    let (a, b) = (X[s], X[s + h]);
+                    X[s] = self.0.add_repr(a, b);
+                    X[s + h] = self.0.mul_repr(self.0.sub_repr(a, b), tw);
+                }
+                tw = self.0.mul_repr(tw, wm);
+            }
+            wm = self.0.mul_repr(wm, wm);
+            h /= 2;
         }
+    }
 
-        let (X_even, X_odd): (Vec<u64>, Vec<u64>) = (0..n / 2)
-            .map(|i| {
-                let l = X[i];
-                let r = X[i + n / 2];
-                (
-                    self.0.add(l, r),
-                    self.0.mul(self.0.sub(l, r), self.0.pow(w, i)),
-                )
-            })
-            .collect();
-
-        // 再帰的にFFT
-        let new_w = self.0.pow(w, 2);
+    /// フーリエ逆変換の本体を回転因子の表を用いずに計算する
+    ///
+    /// [`FFT::ifft_core`] と同じ結果を，メモリを確保せずに求める．
+    fn ifft_core_direct(&self, X: &mut [F::Elem], w: F::Elem) {
+        let n = X.len();
 
-        let Y_even = self.fft_core(X_even, new_w);
-        let Y_odd = self.fft_core(X_odd, new_w);
+        let mut h = 1;
+        while h < n {
+            // 1 の原始 2h 乗根
+            let wm = self.0.pow_repr(w, (n / (2 * h)) as u64);
+            let mut tw = self.0.one();
+            for j in 0..h {
+                for s in (j..n).step_by(2 * h) {
+                    let (a, b) = (X[s], self.0.mul_repr(X[s + h], tw));
+                    X[s] = self.0.add_repr(a, b);
+                    X[s + h] = self.0.sub_repr(a, b);
+                }
+                tw = self.0.mul_repr(tw, wm);
+            }
+            h *= 2;
+        }
+    }
 
-        // マージ
-        Y_even
-            .into_iter()
-            .zip(Y_odd.into_iter())
-            .flat_map(|(e, o)| [e, o])
+    /// 回転因子 w^0, w^1, ..., w^(n/2 - 1) の表
+    ///
+    /// - `w`: 回転演算子（1 の原始 n 乗根）
+    pub(crate) fn twiddles(&self, n: usize, w: F::Elem) -> Vec<F::Elem> {
+        std::iter::successors(Some(self.0.one()), |&x| Some(self.0.mul_repr(x, w)))
+            .take(n / 2)
             .collect()
     }
 
+    /// 作業領域に回転因子の表を書き込む
+    ///
+    /// **Returns**
+    /// - 回転因子の表（作業領域の先頭 n/2 要素）
+    fn fill_twiddles<'a>(
+        &self,
+        scratch: &'a mut [F::Elem],
+        n: usize,
+        w: F::Elem,
+    ) -> Result<&'a [F::Elem], &'static str> {
+        let pw = scratch
+            .get_mut(..n / 2)
+            .ok_or("The scratch buffer is too short.")?;
+
+        let mut x = self.0.one();
+        for v in pw.iter_mut() {
+            *v = x;
+            x = self.0.mul_repr(x, w);
+        }
+
+        Ok(pw)
+    }
+
+    /// 配列の長さが 2 べきであるか確認する
+    ///
+    /// **Returns**
+    /// - `i`: 配列の長さ 2^i
+    fn check_len(&self, X: &[F::Elem]) -> Result<usize, &'static str> {
+        if !X.len().is_power_of_two() {
+            return Err("The length of the array should be a power of 2.");
+        }
+        let i = X.len().trailing_zeros() as usize;
+        if i > self.0.max_pow2() {
+            return Err("The prime p does not have enough factors of 2 in (p - 1).");
+        }
+        Ok(i)
+    }
+
     /// 長さが 2 べきになるように配列を生成する
     ///
     /// **Arguments**
@@ -72,7 +253,10 @@ impl FFT {
     ///
     /// **Returns**
     /// - `(i, res)`: 配列の長さを 2^i に拡張した結果
-    fn extend_array(&self, array: &[u64]) -> Result<(usize, Vec<u64>), &'static str> {
+    pub(crate) fn extend_array(
+        &self,
+        array: &[F::Elem],
+    ) -> Result<(usize, Vec<F::Elem>), &'static str> {
         let n = array.len();
         // 2^i >= n となるような最小の i
         let mut i = 0;
@@ -81,27 +265,74 @@ impl FFT {
             i += 1;
             n_ *= 2;
         }
-        if i > self.0.k {
+        if i > self.0.max_pow2() {
             return Err("The prime p does not have enough factors of 2 in (p - 1).");
         }
         // 配列を生成
         let mut res = array.to_vec();
         // 残りをゼロ埋め
-        res.extend(std::iter::repeat_n(0, n_ - n));
+        res.extend(std::iter::repeat_n(self.0.zero(), n_ - n));
 
         Ok((i, res))
     }
 }
 
+impl<M: Modulus + Copy> FFT<M> {
+    /// 有限体の元の配列をフーリエ変換する
+    pub fn fft_modint(&self, X: &[Modint<M>]) -> Result<Vec<Modint<M>>, &'static str> {
+        let X: Vec<u64> = X.iter().map(|x| self.raw(x)).collect();
+        let res = self.fft_repr(&X)?;
+
+        Ok(res
+            .into_iter()
+            .map(|v| Modint::from_raw(self.0, v))
+            .collect())
+    }
+
+    /// 有限体の元の配列をフーリエ逆変換する
+    pub fn ifft_modint(&self, F: &[Modint<M>]) -> Result<Vec<Modint<M>>, &'static str> {
+        let F: Vec<u64> = F.iter().map(|x| self.raw(x)).collect();
+        let res = self.ifft_repr(&F)?;
+
+        Ok(res
+            .into_iter()
+            .map(|v| Modint::from_raw(self.0, v))
+            .collect())
+    }
+
+    /// 元の Montgomery 表現を取り出す
+    fn raw(&self, x: &Modint<M>) -> u64 {
+        debug_assert_eq!(x.modulus().modulus(), self.0.modulus());
+        x.raw()
+    }
+}
+
+/// 配列をビット反転順に並べ替える
+///
+/// - `X`: 長さが 2 べきの配列
+pub(crate) fn bit_reverse<T>(X: &mut [T]) {
+    let n = X.len();
+    if n <= 2 {
+        return;
+    }
+    let shift = usize::BITS - n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> shift;
+        if i < j {
+            X.swap(i, j);
+        }
+    }
+}
+
 // ===== テスト =====
 #[cfg(test)]
 mod test {
     use rand::{rng, Rng};
     use rstest::rstest;
 
-    use crate::num::Fp;
+    use crate::num::{Complex, ComplexField, Fp, Modint, StaticFp};
 
-    use super::FFT;
+    use super::{super::DFT, bit_reverse, FFT};
 
     #[test]
     fn test_extend_array() {
@@ -117,6 +348,22 @@ mod test {
         assert!(fft.extend_array(&arr_3).is_err());
     }
 
+    #[test]
+    fn test_bit_reverse() {
+        let mut arr: Vec<usize> = (0..8).collect();
+        bit_reverse(&mut arr);
+        assert_eq!(arr, vec![0, 4, 2, 6, 1, 5, 3, 7]);
+
+        let mut arr: Vec<usize> = (0..1).collect();
+        bit_reverse(&mut arr);
+        assert_eq!(arr, vec![0]);
+
+        let mut arr: Vec<usize> = (0..1024).collect();
+        bit_reverse(&mut arr);
+        bit_reverse(&mut arr);
+        assert_eq!(arr, (0..1024).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_fft() {
         {
@@ -173,7 +420,7 @@ mod test {
 
         {
             let arr = vec![
-                31415926, 53589793, 23846264, 33832795, 02884197, 16939937, 51058209, 74944592,
+                31415926, 53589793, 23846264, 33832795, 2884197, 16939937, 51058209, 74944592,
             ];
 
             let fp = Fp::new(998244353).unwrap();
@@ -192,6 +439,137 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_fft_static() {
+        let arr = vec![31415, 92653, 58979, 32384, 62643, 38327, 95028];
+
+        let fft_dyn = FFT(Fp::new(998244353).unwrap());
+        let fft_static = FFT(StaticFp::<998244353>);
+
+        let res = fft_static.fft(&arr).unwrap();
+        assert_eq!(res, fft_dyn.fft(&arr).unwrap());
+
+        let res2 = fft_static.ifft(&res).unwrap();
+        assert_eq!(res2, fft_dyn.ifft(&res).unwrap());
+        assert_eq!(&res2[..arr.len()], arr);
+    }
+
+    #[test]
+    fn test_fft_modint() {
+        let arr = vec![31415, 92653, 58979, 32384, 62643, 38327, 95028];
+
+        {
+            let fft = FFT(StaticFp::<998244353>);
+            let X: Vec<Modint<_>> = arr.iter().map(|&x| Modint::from(x)).collect();
+
+            let res = fft.fft_modint(&X).unwrap();
+            assert_eq!(
+                res.iter().map(|x| x.value()).collect::<Vec<_>>(),
+                fft.fft(&arr).unwrap()
+            );
+
+            let res2 = fft.ifft_modint(&res).unwrap();
+            assert_eq!(&res2[..X.len()], X);
+        }
+
+        {
+            let fp = Fp::new(5767169).unwrap();
+            let fft = FFT(&fp);
+            let X: Vec<Modint<_>> = arr.iter().map(|&x| Modint::new(&fp, x)).collect();
+
+            let res = fft.fft_modint(&X).unwrap();
+            assert_eq!(
+                res.iter().map(|x| x.value()).collect::<Vec<_>>(),
+                fft.fft(&arr).unwrap()
+            );
+
+            let res2 = fft.ifft_modint(&res).unwrap();
+            assert_eq!(&res2[..X.len()], X);
+        }
+    }
+
+    #[test]
+    fn test_fft_complex() {
+        let arr: Vec<Complex> = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0, 5.0]
+            .iter()
+            .map(|&re| Complex::new(re, 0.0))
+            .collect();
+
+        let fft = FFT(ComplexField);
+        let dft = DFT(ComplexField);
+
+        let res = fft.fft(&arr).unwrap();
+        let expected = dft.dft(&arr).unwrap();
+        eprintln!("fft({:?}) = {:?}", arr, res);
+
+        assert_eq!(res.len(), 16);
+        for (x, y) in res.iter().zip(&expected) {
+            assert!(Complex::new(x.re - y.re, x.im - y.im).abs() < 1e-9);
+        }
+
+        // 定数項は総和になる
+        assert!((res[0].re - 36.0).abs() < 1e-9);
+
+        let res2 = fft.ifft(&res).unwrap();
+        for (i, x) in res2.iter().enumerate() {
+            let y = arr.get(i).copied().unwrap_or_default();
+            assert!(Complex::new(x.re - y.re, x.im - y.im).abs() < 1e-9);
+        }
+    }
+
+    #[rstest(
+        n,
+        p,
+        case(1, 5),
+        case(2, 5),
+        case(4, 5),
+        case(8, 17),
+        case(1024, 5767169),
+        case(1 << 15, 998244353)
+    )]
+    fn test_fft_inplace(n: usize, p: u64) {
+        let mut rng = rng();
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+        let fft = FFT(Fp::new(p).unwrap());
+        let expected = fft.fft(&arr).unwrap();
+
+        // 作業領域なし
+        let mut X = arr.clone();
+        fft.fft_inplace(&mut X, None).unwrap();
+        assert_eq!(X, expected);
+        fft.ifft_inplace(&mut X, None).unwrap();
+        assert_eq!(X, arr);
+
+        // 作業領域あり
+        let mut scratch = vec![0; n];
+        let mut X = arr.clone();
+        fft.fft_inplace(&mut X, Some(&mut scratch)).unwrap();
+        assert_eq!(X, expected);
+        fft.ifft_inplace(&mut X, Some(&mut scratch)).unwrap();
+        assert_eq!(X, arr);
+    }
+
+    #[test]
+    fn test_fft_inplace_error() {
+        let fft = FFT(Fp::new(17).unwrap());
+
+        assert!(fft.fft_inplace(&mut [], None).is_err());
+        assert!(fft.fft_inplace(&mut [1, 2, 3], None).is_err());
+        assert!(fft.ifft_inplace(&mut [1, 2, 3, 4, 5, 6], None).is_err());
+        assert!(fft.fft_inplace(&mut [0; 32], None).is_err());
+        assert!(fft.fft_inplace(&mut [0; 8], Some(&mut [0; 3])).is_err());
+        assert!(fft.fft_inplace(&mut [0; 8], Some(&mut [0; 4])).is_ok());
+
+        // エラーの場合は配列を変更しない
+        let arr: Vec<u64> = (1..=8).collect();
+        let mut X = arr.clone();
+        assert!(fft.fft_inplace(&mut X, Some(&mut [0; 2])).is_err());
+        assert_eq!(X, arr);
+        assert!(fft.ifft_inplace(&mut X, Some(&mut [0; 3])).is_err());
+        assert_eq!(X, arr);
+    }
+
     #[rstest(
         size,
         p,
@@ -0,0 +1,346 @@
+//! 4 段階 FFT（Bailey のアルゴリズム）の実装
+
+use crate::num::{Field, Fp};
+
+use super::{fft::bit_reverse, FFT};
+
+/// 転置を行うタイルの大きさ
+const TILE: usize = 16;
+
+/// 長さを固定した 4 段階 FFT の計画
+///
+/// 長さ n = n1 * n2 の配列を n2 行 n1 列の行列とみなし，
+/// 列方向の長さ n2 の変換，回転因子の乗算，行方向の長さ n1 の変換に分解する．
+/// 列の変換は転置してから行うため，各変換は連続した短い配列の上で計算される．
+/// 結果は [`FFT::fft`] と一致する．
+pub struct FourStep<F: Field = Fp> {
+    /// 行の変換に用いる FFT
+    fft: FFT<F>,
+    /// 変換の長さ
+    n: usize,
+    /// 行列の列数
+    n1: usize,
+    /// 行列の行数
+    n2: usize,
+    /// 1 の原始 n 乗根
+    w: F::Elem,
+    /// w^(-1)
+    winv: F::Elem,
+    /// 長さ n1 の変換の回転因子の表（順変換，逆変換）
+    pw1: (Vec<F::Elem>, Vec<F::Elem>),
+    /// 長さ n2 の変換の回転因子の表（順変換，逆変換）
+    pw2: (Vec<F::Elem>, Vec<F::Elem>),
+    /// n^(-1)
+    inv_n: F::Elem,
+}
+
+impl<F: Field> FourStep<F> {
+    /// 長さ n の変換の計画を作成する
+    ///
+    /// **Arguments**
+    /// - `field`: 変換を行う体
+    /// - `n`: 変換の長さ（2 べき）
+    pub fn new(field: F, n: usize) -> Result<Self, &'static str> {
+        if !n.is_power_of_two() {
+            return Err("The length of the array should be a power of 2.");
+        }
+        let i = n.trailing_zeros() as usize;
+
+        let w = field.root_pow2(i)?;
+        let winv = field.inv_repr(w);
+        let inv_n = field.inv_repr(field.embed(n as u64));
+
+        let (n1, n2) = split(n);
+
+        let fft = FFT(field);
+        let (pw1, pw2) = fft.four_step_twiddles(n1, n2, w);
+        let (ipw1, ipw2) = fft.four_step_twiddles(n1, n2, winv);
+
+        Ok(Self {
+            fft,
+            n,
+            n1,
+            n2,
+            w,
+            winv,
+            pw1: (pw1, ipw1),
+            pw2: (pw2, ipw2),
+            inv_n,
+        })
+    }
+
+    /// 変換の長さ
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// 変換を行う体
+    pub fn field(&self) -> &F {
+        &self.fft.0
+    }
+
+    /// 配列をその場でフーリエ変換する
+    ///
+    /// 長さ n の作業領域を確保する．
+    /// 変換を繰り返す場合は [`FourStep::forward_with_scratch`] で作業領域を再利用できる．
+    pub fn forward(&self, X: &mut [F::Elem]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        self.forward_with_scratch(X, &mut vec![self.field().zero(); self.n])
+    }
+
+    /// 配列をその場でフーリエ逆変換する
+    ///
+    /// 長さ n の作業領域を確保する．
+    /// 変換を繰り返す場合は [`FourStep::inverse_with_scratch`] で作業領域を再利用できる．
+    pub fn inverse(&self, X: &mut [F::Elem]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        self.inverse_with_scratch(X, &mut vec![self.field().zero(); self.n])
+    }
+
+    /// 作業領域を用いて配列をその場でフーリエ変換する
+    ///
+    /// **Arguments**
+    /// - `X`: 配列
+    /// - `scratch`: 転置に用いる作業領域（長さ n 以上）
+    pub fn forward_with_scratch(
+        &self,
+        X: &mut [F::Elem],
+        scratch: &mut [F::Elem],
+    ) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let buf = self.scratch(scratch)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.transform(X, buf, self.w, &self.pw1.0, &self.pw2.0);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 作業領域を用いて配列をその場でフーリエ逆変換する
+    ///
+    /// **Arguments**
+    /// - `X`: 配列
+    /// - `scratch`: 転置に用いる作業領域（長さ n 以上）
+    pub fn inverse_with_scratch(
+        &self,
+        X: &mut [F::Elem],
+        scratch: &mut [F::Elem],
+    ) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let buf = self.scratch(scratch)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.transform(X, buf, self.winv, &self.pw1.1, &self.pw2.1);
+        X.iter_mut()
+            .for_each(|v| *v = field.to_value(field.mul_repr(*v, self.inv_n)));
+
+        Ok(())
+    }
+
+    /// 内部表現の配列をその場で変換する（正規化は行わない）
+    ///
+    /// x_{j1 + n1 j2} から X_{k2 + n2 k1} を次の順に求める．
+    /// 1. 転置して，各行 j1 について j2 に関する長さ n2 の変換を行う
+    /// 2. (j1, k2) 成分に w^(j1 k2) を掛ける
+    /// 3. 転置して，各行 k2 について j1 に関する長さ n1 の変換を行う
+    /// 4. 転置して (k1, k2) 成分を X_{k2 + n2 k1} の位置に並べる
+    ///
+    /// - `buf`: 長さ n の作業領域
+    /// - `w`: 1 の原始 n 乗根
+    /// - `pw1`, `pw2`: 長さ n1, n2 の変換の回転因子の表
+    fn transform(
+        &self,
+        X: &mut [F::Elem],
+        buf: &mut [F::Elem],
+        w: F::Elem,
+        pw1: &[F::Elem],
+        pw2: &[F::Elem],
+    ) {
+        let field = self.field();
+        let (n1, n2) = (self.n1, self.n2);
+
+        transpose(X, buf, n2, n1);
+        // w^(j1)
+        let mut wj = field.one();
+        for row in buf.chunks_exact_mut(n2) {
+            self.fft.four_step_first(row, pw2, wj);
+            wj = field.mul_repr(wj, w);
+        }
+
+        transpose(buf, X, n1, n2);
+        for row in X.chunks_exact_mut(n1) {
+            self.fft.four_step_second(row, pw1);
+        }
+
+        transpose(X, buf, n2, n1);
+        X.copy_from_slice(buf);
+    }
+
+    /// 作業領域の先頭 n 個を取り出す
+    fn scratch<'a>(&self, scratch: &'a mut [F::Elem]) -> Result<&'a mut [F::Elem], &'static str> {
+        scratch
+            .get_mut(..self.n)
+            .ok_or("The scratch buffer is too short.")
+    }
+
+    /// 配列の長さが計画と一致するか確認する
+    fn check_len(&self, X: &[F::Elem]) -> Result<(), &'static str> {
+        if X.len() != self.n {
+            return Err("The length of the array does not match the plan.");
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field> FFT<F> {
+    /// 4 段階 FFT の長さ n1, n2 の変換の回転因子の表
+    ///
+    /// - `w`: 1 の原始 n1 n2 乗根
+    pub(crate) fn four_step_twiddles(
+        &self,
+        n1: usize,
+        n2: usize,
+        w: F::Elem,
+    ) -> (Vec<F::Elem>, Vec<F::Elem>) {
+        (
+            self.twiddles(n1, self.0.pow_repr(w, n2 as u64)),
+            self.twiddles(n2, self.0.pow_repr(w, n1 as u64)),
+        )
+    }
+
+    /// 4 段階 FFT の 1 段目として，列 j1 を変換する
+    ///
+    /// j2 に関する長さ n2 の変換を行い，k2 番目の値に w^(j1 k2) を掛ける．
+    /// 入出力は内部表現で与える．
+    ///
+    /// - `pw2`: 長さ n2 の変換の回転因子の表
+    /// - `wj`: w^(j1)
+    pub(crate) fn four_step_first(&self, col: &mut [F::Elem], pw2: &[F::Elem], wj: F::Elem) {
+        self.fft_core(col, pw2);
+        bit_reverse(col);
+
+        let mut t = self.0.one();
+        for v in col.iter_mut() {
+            *v = self.0.mul_repr(*v, t);
+            t = self.0.mul_repr(t, wj);
+        }
+    }
+
+    /// 4 段階 FFT の 2 段目として，列 k2 を j1 に関する長さ n1 の変換で変換する
+    ///
+    /// 入出力は内部表現で与える．
+    ///
+    /// - `pw1`: 長さ n1 の変換の回転因子の表
+    pub(crate) fn four_step_second(&self, col: &mut [F::Elem], pw1: &[F::Elem]) {
+        self.fft_core(col, pw1);
+        bit_reverse(col);
+    }
+}
+
+/// 長さ n（2 べき）の変換を n2 行 n1 列の行列に分割する（n1 <= n2）
+pub(crate) fn split(n: usize) -> (usize, usize) {
+    let i = n.trailing_zeros() as usize;
+    (1 << (i / 2), 1 << (i - i / 2))
+}
+
+/// rows 行 cols 列の行列 src を転置して dst に書き込む
+///
+/// キャッシュの局所性のため，TILE × TILE の小行列ごとに転置する．
+pub(crate) fn transpose<T: Copy>(src: &[T], dst: &mut [T], rows: usize, cols: usize) {
+    for r0 in (0..rows).step_by(TILE) {
+        for c0 in (0..cols).step_by(TILE) {
+            for r in r0..(r0 + TILE).min(rows) {
+                for c in c0..(c0 + TILE).min(cols) {
+                    dst[c * rows + r] = src[r * cols + c];
+                }
+            }
+        }
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::{
+        ntt::FFT,
+        num::{Fp, StaticFp},
+    };
+
+    use super::{transpose, FourStep};
+
+    #[test]
+    fn test_transpose() {
+        let (rows, cols) = (20, 37);
+        let src: Vec<usize> = (0..rows * cols).collect();
+        let mut dst = vec![0; rows * cols];
+
+        transpose(&src, &mut dst, rows, cols);
+        for r in 0..rows {
+            for c in 0..cols {
+                assert_eq!(dst[c * rows + r], src[r * cols + c]);
+            }
+        }
+    }
+
+    #[rstest(
+        n,
+        p,
+        case(1, 998244353),
+        case(2, 998244353),
+        case(8, 17),
+        case(16, 17),
+        case(1 << 10, 998244353),
+        case(1 << 11, 998244353),
+        case(1 << 17, 998244353),
+        case(1 << 12, 18446744069414584321)
+    )]
+    fn test_four_step(n: usize, p: u64) {
+        let mut rng = rng();
+        let plan = FourStep::new(Fp::new(p).unwrap(), n).unwrap();
+        let fft = FFT(Fp::new(p).unwrap());
+        assert_eq!(plan.size(), n);
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+
+        let mut X = arr.clone();
+        plan.forward(&mut X).unwrap();
+        assert_eq!(X, fft.fft(&arr).unwrap());
+
+        plan.inverse(&mut X).unwrap();
+        assert_eq!(X, arr);
+
+        // 作業領域を再利用する
+        let mut scratch = vec![0; n + 3];
+        for _ in 0..2 {
+            plan.forward_with_scratch(&mut X, &mut scratch).unwrap();
+            assert_eq!(X, fft.fft(&arr).unwrap());
+            plan.inverse_with_scratch(&mut X, &mut scratch).unwrap();
+            assert_eq!(X, arr);
+        }
+    }
+
+    #[test]
+    fn test_four_step_error() {
+        assert!(FourStep::new(StaticFp::<998244353>, 0).is_err());
+        assert!(FourStep::new(StaticFp::<998244353>, 12).is_err());
+        // 998244353 - 1 = 2^23 * 119
+        assert!(FourStep::new(StaticFp::<998244353>, 1 << 24).is_err());
+        assert!(FourStep::new(StaticFp::<17>, 32).is_err());
+
+        let plan = FourStep::new(StaticFp::<998244353>, 8).unwrap();
+        assert!(plan.forward(&mut [1, 2, 3, 4]).is_err());
+        assert!(plan.inverse(&mut [0; 16]).is_err());
+
+        // 作業領域が短い場合は配列を変更しない
+        let mut X = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert!(plan.forward_with_scratch(&mut X, &mut [0; 7]).is_err());
+        assert!(plan.inverse_with_scratch(&mut X, &mut []).is_err());
+        assert_eq!(X, [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
@@ -0,0 +1,480 @@
+//! 混合基数 NTT の実装
+
+use crate::num::{factorize, Field, Fp, Modulus};
+
+use super::{rader::RaderCore, FFT};
+
+/// 定義どおりに計算する素因数の上限
+///
+/// これより大きい素因数は Rader のアルゴリズムで計算する．
+/// ただし，Rader のアルゴリズムに必要な巡回畳み込みができない場合は定義どおりに計算する．
+const MAX_PRIME_FACTOR: usize = 31;
+
+/// 長さ n の混合基数 NTT の計画
+///
+/// p - 1 を割り切る n に対して，長さ n の変換を配列の拡張なしに計算する．
+/// 基数 4, 2, 3, 5 のバタフライ演算を用い，それ以外の小さな素因数は定義どおりに，
+/// 大きな素因数は Rader のアルゴリズムで計算する．
+/// Rader のアルゴリズムに必要な 2 べきの長さの畳み込みができない素因数は定義どおりに計算する．
+pub struct MixedRadix<M: Modulus = Fp> {
+    /// 変換を行う体（Rader のアルゴリズムの畳み込みにも用いる）
+    fft: FFT<M>,
+    /// 変換の本体
+    core: MixedRadixCore,
+    /// n^(-1)
+    inv_n: u64,
+}
+
+impl<M: Modulus> MixedRadix<M> {
+    /// 長さ n の変換の計画を作成する
+    ///
+    /// **Arguments**
+    /// - `field`: 変換を行う体
+    /// - `n`: 変換の長さ（p - 1 の約数）
+    pub fn new(field: M, n: usize) -> Result<Self, &'static str> {
+        let fft = FFT(field);
+        let core = MixedRadixCore::new(&fft, n)?;
+        let inv_n = fft.0.inv_repr(fft.0.embed(n as u64));
+
+        Ok(Self { fft, core, inv_n })
+    }
+
+    /// 変換の長さ
+    pub fn size(&self) -> usize {
+        self.core.n
+    }
+
+    /// 変換を行う体
+    pub fn field(&self) -> &M {
+        &self.fft.0
+    }
+
+    /// 配列をその場でフーリエ変換する
+    pub fn forward(&self, X: &mut [u64]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.forward_repr(X);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 配列をその場でフーリエ逆変換する
+    pub fn inverse(&self, X: &mut [u64]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.inverse_repr(X);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 内部表現の配列をその場でフーリエ変換する
+    pub(crate) fn forward_repr(&self, X: &mut [u64]) {
+        self.core.apply(&self.fft, X, false);
+    }
+
+    /// 内部表現の配列をその場でフーリエ逆変換する
+    pub(crate) fn inverse_repr(&self, X: &mut [u64]) {
+        self.core.apply(&self.fft, X, true);
+
+        let field = self.field();
+        X.iter_mut()
+            .for_each(|v| *v = field.mul_repr(*v, self.inv_n));
+    }
+
+    /// 配列の長さが計画と一致するか確認する
+    fn check_len(&self, X: &[u64]) -> Result<(), &'static str> {
+        if X.len() != self.core.n {
+            return Err("The length of the array does not match the plan.");
+        }
+        Ok(())
+    }
+}
+
+/// 混合基数 NTT の本体
+///
+/// 体を保持せず，Bluestein のアルゴリズムが使えない場合の変換にも用いる．
+/// 入出力は内部表現で与え，逆変換の正規化は行わない．
+pub(crate) struct MixedRadixCore {
+    /// 変換の長さ
+    n: usize,
+    /// 各段の基数（先頭から順に分解する）
+    radices: Vec<usize>,
+    /// ω^i（i < n）
+    pw: Vec<u64>,
+    /// ω^(-i)（i < n）
+    ipw: Vec<u64>,
+    /// 順変換のバタフライ演算の定数
+    consts: Consts,
+    /// 逆変換のバタフライ演算の定数
+    iconsts: Consts,
+    /// 大きな素因数の基数に対する Rader のアルゴリズム（作成できた基数のみ）
+    raders: Vec<RaderCore>,
+}
+
+/// バタフライ演算に用いる定数（ω_r は 1 の原始 r 乗根）
+#[derive(Debug, Clone, Copy, Default)]
+struct Consts {
+    /// 2^(-1)
+    inv2: u64,
+    /// ω_4
+    w4: u64,
+    /// (ω_3 - ω_3^2) / 2
+    h3: u64,
+    /// (ω_5 + ω_5^4) / 2, (ω_5^2 + ω_5^3) / 2
+    c5: [u64; 2],
+    /// (ω_5 - ω_5^4) / 2, (ω_5^2 - ω_5^3) / 2
+    j5: [u64; 2],
+}
+
+impl MixedRadixCore {
+    /// 長さ n の変換の本体を作成する
+    pub(crate) fn new<M: Modulus>(fft: &FFT<M>, n: usize) -> Result<Self, &'static str> {
+        let field = &fft.0;
+        let w = field.to_repr(field.root_of_unity(n as u64)?);
+        let radices = Self::radices(n);
+
+        let pw = Self::powers(field, w, n);
+        let ipw = Self::powers(field, field.inv_repr(w), n);
+        let consts = Self::consts(field, &pw);
+        let iconsts = Self::consts(field, &ipw);
+
+        let mut raders: Vec<RaderCore> = vec![];
+        let mut large: Vec<usize> = radices
+            .iter()
+            .copied()
+            .filter(|&r| r > MAX_PRIME_FACTOR)
+            .collect();
+        large.dedup();
+        for r in large {
+            // r - 1 の巡回畳み込みができない場合は定義どおりに計算する
+            if let Ok(rader) = RaderCore::new(fft, r) {
+                raders.push(rader);
+            }
+        }
+
+        Ok(Self {
+            n,
+            radices,
+            pw,
+            ipw,
+            consts,
+            iconsts,
+            raders,
+        })
+    }
+
+    /// 内部表現の配列をその場で変換する
+    ///
+    /// - `inverse`: ω の代わりに ω^(-1) を用いるか（正規化は行わない）
+    pub(crate) fn apply<M: Modulus>(&self, fft: &FFT<M>, X: &mut [u64], inverse: bool) {
+        let input = X.to_vec();
+        self.transform(fft, &input, 1, X, 0, inverse);
+    }
+
+    /// 時間間引きで再帰的に変換する
+    ///
+    /// `input[0], input[stride], ...` を変換した結果を `out` に格納する．
+    ///
+    /// - `level`: 分解の段数（`stride` は先頭から `level` 個の基数の積）
+    /// - `inverse`: ω の代わりに ω^(-1) を用いるか（正規化は行わない）
+    fn transform<M: Modulus>(
+        &self,
+        fft: &FFT<M>,
+        input: &[u64],
+        stride: usize,
+        out: &mut [u64],
+        level: usize,
+        inverse: bool,
+    ) {
+        let len = out.len();
+        if len == 1 {
+            out[0] = input[0];
+            return;
+        }
+
+        let r = self.radices[level];
+        let m = len / r;
+
+        // 長さ m の部分問題に分ける
+        for (q, chunk) in out.chunks_exact_mut(m).enumerate() {
+            self.transform(
+                fft,
+                &input[q * stride..],
+                stride * r,
+                chunk,
+                level + 1,
+                inverse,
+            );
+        }
+
+        // X_{k + m k'} = Σ_q ω_len^{qk} ω_r^{qk'} Y_q[k]
+        let field = &fft.0;
+        let pw = if inverse { &self.ipw } else { &self.pw };
+        let mut buf = vec![0; r];
+        for k in 0..m {
+            for (q, b) in buf.iter_mut().enumerate() {
+                *b = field.mul_repr(out[q * m + k], pw[q * k * stride]);
+            }
+            self.butterfly(fft, &mut buf, inverse);
+            for (q, &b) in buf.iter().enumerate() {
+                out[q * m + k] = b;
+            }
+        }
+    }
+
+    /// 長さ r の DFT（バタフライ演算）をその場で計算する
+    fn butterfly<M: Modulus>(&self, fft: &FFT<M>, a: &mut [u64], inverse: bool) {
+        let f = &fft.0;
+        let (pw, consts) = if inverse {
+            (&self.ipw, &self.iconsts)
+        } else {
+            (&self.pw, &self.consts)
+        };
+
+        match *a {
+            [x0, x1] => {
+                a[0] = f.add_repr(x0, x1);
+                a[1] = f.sub_repr(x0, x1);
+            }
+            [x0, x1, x2] => {
+                let s = f.add_repr(x1, x2);
+                let d = f.mul_repr(f.sub_repr(x1, x2), consts.h3);
+                // x0 - s / 2
+                let t = f.sub_repr(x0, f.mul_repr(s, consts.inv2));
+
+                a[0] = f.add_repr(x0, s);
+                a[1] = f.add_repr(t, d);
+                a[2] = f.sub_repr(t, d);
+            }
+            [x0, x1, x2, x3] => {
+                let (s0, d0) = (f.add_repr(x0, x2), f.sub_repr(x0, x2));
+                let (s1, d1) = (
+                    f.add_repr(x1, x3),
+                    f.mul_repr(f.sub_repr(x1, x3), consts.w4),
+                );
+
+                a[0] = f.add_repr(s0, s1);
+                a[1] = f.add_repr(d0, d1);
+                a[2] = f.sub_repr(s0, s1);
+                a[3] = f.sub_repr(d0, d1);
+            }
+            [x0, x1, x2, x3, x4] => {
+                let (s1, d1) = (f.add_repr(x1, x4), f.sub_repr(x1, x4));
+                let (s2, d2) = (f.add_repr(x2, x3), f.sub_repr(x2, x3));
+                let [c1, c2] = consts.c5;
+                let [j1, j2] = consts.j5;
+
+                let t1 = f.add_repr(x0, f.add_repr(f.mul_repr(c1, s1), f.mul_repr(c2, s2)));
+                let t2 = f.add_repr(x0, f.add_repr(f.mul_repr(c2, s1), f.mul_repr(c1, s2)));
+                let u1 = f.add_repr(f.mul_repr(j1, d1), f.mul_repr(j2, d2));
+                let u2 = f.sub_repr(f.mul_repr(j2, d1), f.mul_repr(j1, d2));
+
+                a[0] = f.add_repr(x0, f.add_repr(s1, s2));
+                a[1] = f.add_repr(t1, u1);
+                a[2] = f.add_repr(t2, u2);
+                a[3] = f.sub_repr(t2, u2);
+                a[4] = f.sub_repr(t1, u1);
+            }
+            _ => {
+                let r = a.len();
+                if let Some(rader) = self.raders.iter().find(|rader| rader.size() == r) {
+                    rader.apply(fft, a, inverse);
+                    return;
+                }
+
+                // 定義どおりに計算する
+                let step = self.n / r;
+                let x = a.to_vec();
+                for (k, y) in a.iter_mut().enumerate() {
+                    *y = x.iter().enumerate().fold(f.zero(), |acc, (q, &v)| {
+                        f.add_repr(acc, f.mul_repr(v, pw[(q * k % r) * step]))
+                    });
+                }
+            }
+        }
+    }
+
+    /// n を基数の列に分解する
+    ///
+    /// 2 の冪はなるべく 4 でまとめ，残りの素因数を小さい順に並べる．
+    fn radices(mut n: usize) -> Vec<usize> {
+        let mut res = vec![];
+        while n.is_multiple_of(4) {
+            res.push(4);
+            n /= 4;
+        }
+        for d in 2..=MAX_PRIME_FACTOR {
+            while n.is_multiple_of(d) {
+                res.push(d);
+                n /= d;
+            }
+        }
+        for (q, e) in factorize(n as u64) {
+            res.extend(std::iter::repeat_n(q as usize, e as usize));
+        }
+        res
+    }
+
+    /// w^i（i < n）
+    fn powers<M: Modulus>(field: &M, w: u64, n: usize) -> Vec<u64> {
+        let mut x = field.one();
+        (0..n)
+            .map(|_| {
+                let res = x;
+                x = field.mul_repr(x, w);
+                res
+            })
+            .collect()
+    }
+
+    /// ω^i の表からバタフライ演算の定数を求める
+    fn consts<M: Modulus>(field: &M, pw: &[u64]) -> Consts {
+        let n = pw.len();
+        let inv2 = field.inv_repr(field.embed(2));
+        let half = |x: u64| field.mul_repr(x, inv2);
+
+        let mut res = Consts {
+            inv2,
+            ..Default::default()
+        };
+        if n.is_multiple_of(4) {
+            res.w4 = pw[n / 4];
+        }
+        if n.is_multiple_of(3) {
+            let w = |i: usize| pw[i * n / 3];
+            res.h3 = half(field.sub_repr(w(1), w(2)));
+        }
+        if n.is_multiple_of(5) {
+            let w = |i: usize| pw[i * n / 5];
+            res.c5 = [
+                half(field.add_repr(w(1), w(4))),
+                half(field.add_repr(w(2), w(3))),
+            ];
+            res.j5 = [
+                half(field.sub_repr(w(1), w(4))),
+                half(field.sub_repr(w(2), w(3))),
+            ];
+        }
+        res
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::{
+        ntt::{dft::dft_naive, Bluestein, FFT},
+        num::{Fp, StaticFp},
+    };
+
+    use super::{MixedRadix, MixedRadixCore};
+
+    #[test]
+    fn test_radices() {
+        assert_eq!(MixedRadixCore::radices(1), vec![]);
+        assert_eq!(MixedRadixCore::radices(8), vec![4, 2]);
+        assert_eq!(MixedRadixCore::radices(7680), vec![4, 4, 4, 4, 2, 3, 5]);
+        assert_eq!(MixedRadixCore::radices(11 * 31), vec![11, 31]);
+        assert_eq!(MixedRadixCore::radices(37 * 37 * 6), vec![2, 3, 37, 37]);
+        assert_eq!(MixedRadixCore::radices(65537 * 4), vec![4, 65537]);
+    }
+
+    #[rstest(
+        n,
+        p,
+        case(1, 7681),
+        case(2, 7681),
+        case(3, 7681),
+        case(4, 7681),
+        case(5, 7681),
+        case(15, 7681),
+        case(960, 7681),
+        case(768, 12289),
+        case(11, 5767169),
+        case(704, 5767169),
+        case(952, 998244353),
+        case(60, 18446744069414584321),
+        case(17 * 15 * 4, 18446744069414584321),
+        case(257 * 5, 18446744069414584321),
+        case(641 * 4, 1312769), // 641 * 2^11 + 1
+        // 167 - 1 = 2 * 83 では Rader のアルゴリズムの畳み込みができない
+        case(83, 167),
+        case(166, 167)
+    )]
+    fn test_mixed_radix(n: usize, p: u64) {
+        let mut rng = rng();
+        let fp = Fp::new(p).unwrap();
+        let plan = MixedRadix::new(&fp, n).unwrap();
+        assert_eq!(plan.size(), n);
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+
+        let mut X = arr.clone();
+        plan.forward(&mut X).unwrap();
+        assert_eq!(X, dft_naive(&fp, &arr));
+
+        plan.inverse(&mut X).unwrap();
+        assert_eq!(X, arr);
+    }
+
+    #[rstest(
+        n,
+        p,
+        case(7680, 7681),
+        case(12288, 12289),
+        case(11 << 12, 5767169),
+        case(1 << 15, 998244353),
+        case(65537 * 4, 18446744069414584321)
+    )]
+    fn test_mixed_radix_large(n: usize, p: u64) {
+        let mut rng = rng();
+        let plan = MixedRadix::new(Fp::new(p).unwrap(), n).unwrap();
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+
+        let mut X = arr.clone();
+        plan.forward(&mut X).unwrap();
+
+        // 長さが 2 べきの場合は FFT，そうでなければ Bluestein のアルゴリズムと比較する
+        if n.is_power_of_two() {
+            assert_eq!(X, FFT(Fp::new(p).unwrap()).fft(&arr).unwrap());
+        } else if let Ok(bluestein) = Bluestein::new(Fp::new(p).unwrap(), n) {
+            let mut Y = arr.clone();
+            bluestein.forward(&mut Y).unwrap();
+            assert_eq!(X, Y);
+        }
+
+        plan.inverse(&mut X).unwrap();
+        assert_eq!(X, arr);
+    }
+
+    #[test]
+    fn test_mixed_radix_error() {
+        // 998244353 - 1 = 2^23 * 7 * 17
+        assert!(MixedRadix::new(StaticFp::<998244353>, 0).is_err());
+        assert_eq!(
+            MixedRadix::new(StaticFp::<998244353>, 3).err(),
+            Some("The order of the root of unity should divide p - 1.")
+        );
+
+        // 167 - 1 = 2 * 83 では Rader のアルゴリズムの畳み込みができないが，定義どおりに計算する
+        let plan = MixedRadix::new(StaticFp::<167>, 83).unwrap();
+        let mut X = [1; 83];
+        plan.forward(&mut X).unwrap();
+        assert_eq!(X[0], 83);
+        assert!(X[1..].iter().all(|&v| v == 0));
+
+        let plan = MixedRadix::new(StaticFp::<998244353>, 7).unwrap();
+        assert!(plan.forward(&mut [1, 2, 3]).is_err());
+        assert!(plan.inverse(&mut [0; 8]).is_err());
+    }
+}
@@ -1,5 +1,25 @@
+mod arbitrary_mod;
+mod bluestein;
+mod convolution;
 mod dft;
 mod fft;
+mod four_step;
+mod mixed_radix;
+mod out_of_core;
+mod parallel;
+mod plan;
+mod rader;
+mod radix;
+mod tft;
 
+pub use arbitrary_mod::{convolve_mod, convolve_u128};
+pub use bluestein::Bluestein;
 pub use dft::DFT;
 pub use fft::FFT;
+pub use four_step::FourStep;
+pub use mixed_radix::MixedRadix;
+pub use out_of_core::OutOfCoreFFT;
+pub use parallel::ParallelFFT;
+pub use plan::FFTPlan;
+pub use rader::Rader;
+pub use radix::Radix;
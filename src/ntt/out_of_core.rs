@@ -0,0 +1,592 @@
+//! ファイル上の配列に対する FFT の実装
+
+use std::{
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+};
+
+use memmap2::{Mmap, MmapMut};
+
+use crate::num::{Field, Fp, Modulus};
+
+use super::{four_step::split, FFT};
+
+/// 1 つの値が占めるバイト数
+const WORD: usize = 8;
+
+/// ファイル上の配列に対する FFT
+///
+/// 配列はファイルに little endian の u64 として隙間なく格納する．
+/// ファイルをメモリにマップし，4 段階 FFT（[`super::FourStep`]）の各段を
+/// 行列の列の帯ごとに読み込んで計算するため，一度に確保するメモリは予算の範囲に収まる．
+/// 結果は [`FFT::fft`] などと一致する．
+pub struct OutOfCoreFFT<M: Modulus = Fp> {
+    /// 列の変換に用いる FFT
+    fft: FFT<M>,
+    /// 作業領域に用いるメモリの上限（バイト）
+    budget: usize,
+}
+
+/// 長さ n の変換の 4 段階への分割
+struct Layout {
+    /// 変換の長さ
+    n: usize,
+    /// 入力を n2 行 n1 列の行列とみなしたときの列数
+    n1: usize,
+    /// 入力を n2 行 n1 列の行列とみなしたときの行数
+    n2: usize,
+    /// 1 の原始 n 乗根
+    w: u64,
+    /// 長さ n1, n2 の変換の回転因子の表
+    pw1: Vec<u64>,
+    pw2: Vec<u64>,
+    /// 列の帯の読み込みに用いるメモリの上限（バイト）
+    band: usize,
+}
+
+impl<M: Modulus> OutOfCoreFFT<M> {
+    /// ファイル上の FFT を作成する
+    ///
+    /// **Arguments**
+    /// - `field`: 変換を行う体
+    /// - `budget`: 作業領域に用いるメモリの上限（バイト）．
+    ///   回転因子の表と列の帯の読み込みに用いるメモリの合計であり，
+    ///   長さ n = n1 n2（n1 <= n2）の変換には 8(n1 + 2 n2) バイト以上が必要である．
+    pub fn new(field: M, budget: usize) -> Self {
+        Self {
+            fft: FFT(field),
+            budget,
+        }
+    }
+
+    /// 作業領域に用いるメモリの上限（バイト）
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// 変換を行う体
+    pub fn field(&self) -> &M {
+        &self.fft.0
+    }
+
+    /// ファイル上の配列をフーリエ変換し，結果を別のファイルに書き込む
+    ///
+    /// 配列の長さが 2 べきでない場合はゼロ埋めして拡張する．
+    ///
+    /// **Arguments**
+    /// - `src`: 入力のファイル
+    /// - `dst`: 出力のファイル（`src` とは別のファイル．存在する場合は上書きする）
+    ///
+    /// **Returns**
+    /// - 変換の長さ
+    pub fn fft(&self, src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<usize, &'static str> {
+        self.transform_file(src.as_ref(), dst.as_ref(), false)
+    }
+
+    /// ファイル上の配列をフーリエ逆変換し，結果を別のファイルに書き込む
+    ///
+    /// 配列の長さが 2 べきでない場合はゼロ埋めして拡張する．
+    ///
+    /// **Arguments**
+    /// - `src`: 入力のファイル
+    /// - `dst`: 出力のファイル（`src` とは別のファイル．存在する場合は上書きする）
+    ///
+    /// **Returns**
+    /// - 変換の長さ
+    pub fn ifft(
+        &self,
+        src: impl AsRef<Path>,
+        dst: impl AsRef<Path>,
+    ) -> Result<usize, &'static str> {
+        self.transform_file(src.as_ref(), dst.as_ref(), true)
+    }
+
+    /// ファイル上の 2 つの配列の畳み込みを計算し，結果を別のファイルに書き込む
+    ///
+    /// `dst` は `a`, `b` とは別のファイルでなければならない．
+    /// 計算の途中で `dst` に拡張子 `.tmp` を付けたファイルを作業用に作成するため，
+    /// これも `a`, `b` とは別のファイルでなければならない．
+    ///
+    /// **Returns**
+    /// - 結果の長さ
+    pub fn convolve(
+        &self,
+        a: impl AsRef<Path>,
+        b: impl AsRef<Path>,
+        dst: impl AsRef<Path>,
+    ) -> Result<usize, &'static str> {
+        let (a, b, dst) = (a.as_ref(), b.as_ref(), dst.as_ref());
+
+        let mut tmp = dst.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        for out in [dst, &tmp] {
+            check_distinct(a, out)?;
+            check_distinct(b, out)?;
+        }
+
+        let (la, lb) = (file_len(a)?, file_len(b)?);
+        if la == 0 || lb == 0 {
+            create(dst, 0)?;
+            return Ok(0);
+        }
+        let len = la + lb - 1;
+        let layout = self.layout(len.next_power_of_two())?;
+
+        let res = self.convolve_with(a, b, dst, &tmp, &layout);
+        // 作業用のファイルは結果によらず削除する
+        let _ = fs::remove_file(&tmp);
+        res?;
+
+        // 余分な 0 を取り除く
+        let file = OpenOptions::new().write(true).open(dst).map_err(io_err)?;
+        file.set_len((len * WORD) as u64).map_err(io_err)?;
+
+        Ok(len)
+    }
+
+    /// 作業用のファイル `tmp` を用いて畳み込みを計算する
+    fn convolve_with(
+        &self,
+        a: &Path,
+        b: &Path,
+        dst: &Path,
+        tmp: &Path,
+        layout: &Layout,
+    ) -> Result<(), &'static str> {
+        let field = self.field();
+
+        self.transform(a, dst, layout, false)?;
+        self.transform(b, tmp, layout, false)?;
+        {
+            let fa = map(dst)?;
+            let mut fb = map_mut(tmp)?;
+            for i in 0..layout.n {
+                let (x, y) = (field.to_repr(load(&fa, i)), field.to_repr(load(&fb, i)));
+                store(&mut fb, i, field.to_value(field.mul_repr(x, y)));
+            }
+            fb.flush().map_err(io_err)?;
+        }
+        let ilayout = self.inverse_layout(layout);
+        self.transform(tmp, dst, &ilayout, true)
+    }
+
+    /// ファイル上の配列を変換する
+    fn transform_file(&self, src: &Path, dst: &Path, inverse: bool) -> Result<usize, &'static str> {
+        check_distinct(src, dst)?;
+        let n = file_len(src)?.next_power_of_two();
+        let layout = self.layout(n)?;
+        let layout = if inverse {
+            self.inverse_layout(&layout)
+        } else {
+            layout
+        };
+
+        self.transform(src, dst, &layout, inverse)?;
+        Ok(n)
+    }
+
+    /// 長さ n の順変換の分割を求める
+    fn layout(&self, n: usize) -> Result<Layout, &'static str> {
+        let field = self.field();
+        let i = n.trailing_zeros() as usize;
+        let w = field.root_pow2(i)?;
+
+        let (n1, n2) = split(n);
+        // 順変換と逆変換の回転因子の表は合わせて n1 + n2 個の値を持つ．
+        // さらに各段で n2 個，n1 個の値を持つ列を少なくとも 1 本読み込む
+        let band = self
+            .budget
+            .checked_sub((n1 + n2) * WORD)
+            .filter(|&band| band >= n2 * WORD)
+            .ok_or("The memory budget is too small for the transform.")?;
+
+        let (pw1, pw2) = self.fft.four_step_twiddles(n1, n2, w);
+
+        Ok(Layout {
+            n,
+            n1,
+            n2,
+            w,
+            pw1,
+            pw2,
+            band,
+        })
+    }
+
+    /// 順変換の分割から逆変換の分割を求める
+    fn inverse_layout(&self, layout: &Layout) -> Layout {
+        let field = self.field();
+        let w = field.inv_repr(layout.w);
+        let (pw1, pw2) = self.fft.four_step_twiddles(layout.n1, layout.n2, w);
+
+        Layout {
+            w,
+            pw1,
+            pw2,
+            ..*layout
+        }
+    }
+
+    /// 4 段階 FFT でファイル上の配列を変換する
+    ///
+    /// x_{j1 + n1 j2} から X_{k2 + n2 k1} を次の 2 段で求める．
+    /// 1. 入力の各列 j1 に [`FFT::four_step_first`] を適用して
+    ///    出力の行 j1 に書き込む（出力は n1 行 n2 列の行列になる）
+    /// 2. 出力の各列 k2 に [`FFT::four_step_second`] を適用して，その場に書き戻す
+    ///
+    /// - `inverse`: 結果を n で割るか
+    fn transform(
+        &self,
+        src: &Path,
+        dst: &Path,
+        layout: &Layout,
+        inverse: bool,
+    ) -> Result<(), &'static str> {
+        let field = self.field();
+        let Layout {
+            n, n1, n2, w, band, ..
+        } = *layout;
+
+        let len = file_len(src)?;
+        let input = map(src)?;
+        let mut output = create(dst, n)?;
+
+        // 1 段目：入力の列を幅 width の帯ごとに読み込む
+        let width = (band / (n2 * WORD)).min(n1);
+        let mut buf = vec![0; width * n2];
+        for c0 in (0..n1).step_by(width) {
+            let c = width.min(n1 - c0);
+            let panel = &mut buf[..c * n2];
+
+            for j2 in 0..n2 {
+                for t in 0..c {
+                    let i = j2 * n1 + c0 + t;
+                    panel[t * n2 + j2] = if i < len {
+                        field.to_repr(load(&input, i))
+                    } else {
+                        field.zero()
+                    };
+                }
+            }
+
+            for (t, col) in panel.chunks_exact_mut(n2).enumerate() {
+                // w^(j1)
+                let wj = field.pow_repr(w, (c0 + t) as u64);
+                self.fft.four_step_first(col, &layout.pw2, wj);
+            }
+
+            // 列 j1 の結果は出力の行 j1 として連続して並ぶ
+            for (i, &v) in panel.iter().enumerate() {
+                store(&mut output, c0 * n2 + i, v);
+            }
+        }
+        drop(input);
+
+        // 2 段目：出力の列を幅 width の帯ごとに読み込み，その場で変換する
+        let inv_n = field.inv_repr(field.embed(n as u64));
+        let width = (band / (n1 * WORD)).min(n2);
+        let mut buf = vec![0; width * n1];
+        for c0 in (0..n2).step_by(width) {
+            let c = width.min(n2 - c0);
+            let panel = &mut buf[..c * n1];
+
+            for j1 in 0..n1 {
+                for t in 0..c {
+                    panel[t * n1 + j1] = load(&output, j1 * n2 + c0 + t);
+                }
+            }
+
+            for col in panel.chunks_exact_mut(n1) {
+                self.fft.four_step_second(col, &layout.pw1);
+
+                for v in col.iter_mut() {
+                    if inverse {
+                        *v = field.mul_repr(*v, inv_n);
+                    }
+                    *v = field.to_value(*v);
+                }
+            }
+
+            for k1 in 0..n1 {
+                for t in 0..c {
+                    store(&mut output, k1 * n2 + c0 + t, panel[t * n1 + k1]);
+                }
+            }
+        }
+
+        output.flush().map_err(io_err)
+    }
+}
+
+/// 入出力のエラーを変換する
+fn io_err(_: std::io::Error) -> &'static str {
+    "Failed to access the file."
+}
+
+/// 入力のファイルと出力のファイルが同じでないことを確認する
+///
+/// 出力のファイルを作成すると入力が失われるため，同じファイルを指す場合はエラーとする．
+fn check_distinct(src: &Path, dst: &Path) -> Result<(), &'static str> {
+    if same_file(src, dst) {
+        return Err("The output file should be different from the input files.");
+    }
+    Ok(())
+}
+
+/// 2 つのパスが同じファイルを指すか（どちらかが存在しない場合は false）
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    // ハードリンクも検出するため，デバイスと inode を比較する
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/// 2 つのパスが同じファイルを指すか（どちらかが存在しない場合は false）
+#[cfg(not(unix))]
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// ファイルに格納された配列の長さ
+fn file_len(path: &Path) -> Result<usize, &'static str> {
+    let len = fs::metadata(path).map_err(io_err)?.len() as usize;
+    if !len.is_multiple_of(WORD) {
+        return Err("The length of the file should be a multiple of 8 bytes.");
+    }
+    Ok(len / WORD)
+}
+
+/// ファイルを読み込み用にマップする
+fn map(path: &Path) -> Result<Mmap, &'static str> {
+    let file = File::open(path).map_err(io_err)?;
+    // 変換の間にファイルが変更されないことを前提とする
+    unsafe { Mmap::map(&file) }.map_err(io_err)
+}
+
+/// ファイルを書き込み用にマップする
+fn map_mut(path: &Path) -> Result<MmapMut, &'static str> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(io_err)?;
+    // 変換の間にファイルが変更されないことを前提とする
+    unsafe { MmapMut::map_mut(&file) }.map_err(io_err)
+}
+
+/// 長さ n の配列を格納するファイルを作成し，書き込み用にマップする
+fn create(path: &Path, n: usize) -> Result<MmapMut, &'static str> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(io_err)?;
+    file.set_len((n * WORD) as u64).map_err(io_err)?;
+    // 変換の間にファイルが変更されないことを前提とする
+    unsafe { MmapMut::map_mut(&file) }.map_err(io_err)
+}
+
+/// i 番目の値を読み込む
+#[inline]
+fn load(map: &[u8], i: usize) -> u64 {
+    let mut bytes = [0; WORD];
+    bytes.copy_from_slice(&map[i * WORD..(i + 1) * WORD]);
+    u64::from_le_bytes(bytes)
+}
+
+/// i 番目に値を書き込む
+#[inline]
+fn store(map: &mut [u8], i: usize, v: u64) {
+    map[i * WORD..(i + 1) * WORD].copy_from_slice(&v.to_le_bytes());
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use std::{fs, path::Path};
+
+    use rand::{rng, Rng};
+    use rstest::rstest;
+    use tempfile::tempdir;
+
+    use crate::{
+        ntt::FFT,
+        num::{Fp, StaticFp},
+    };
+
+    use super::OutOfCoreFFT;
+
+    /// 配列をファイルに書き込む
+    fn write(path: &Path, X: &[u64]) {
+        let bytes: Vec<u8> = X.iter().flat_map(|v| v.to_le_bytes()).collect();
+        fs::write(path, bytes).unwrap();
+    }
+
+    /// ファイルから配列を読み込む
+    fn read(path: &Path) -> Vec<u64> {
+        fs::read(path)
+            .unwrap()
+            .chunks_exact(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    #[rstest(
+        n,
+        p,
+        budget,
+        case(1, 998244353, 24),
+        case(2, 998244353, 40),
+        case(5, 17, 80),
+        case(16, 17, 104),
+        case(1000, 998244353, 1024),
+        case(1 << 11, 998244353, 1 << 11),
+        case(1 << 12, 998244353, 1 << 20),
+        case(3000, 18446744069414584321, 2000)
+    )]
+    fn test_out_of_core(n: usize, p: u64, budget: usize) {
+        let mut rng = rng();
+        let dir = tempdir().unwrap();
+        let (src, dst, inv) = (
+            dir.path().join("src"),
+            dir.path().join("dst"),
+            dir.path().join("inv"),
+        );
+
+        let plan = OutOfCoreFFT::new(Fp::new(p).unwrap(), budget);
+        let fft = FFT(Fp::new(p).unwrap());
+        assert_eq!(plan.budget(), budget);
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+        write(&src, &arr);
+
+        let len = plan.fft(&src, &dst).unwrap();
+        assert_eq!(len, n.next_power_of_two());
+        let res = read(&dst);
+        assert_eq!(res, fft.fft(&arr).unwrap());
+
+        plan.ifft(&dst, &inv).unwrap();
+        assert_eq!(&read(&inv)[..n], arr);
+        assert_eq!(read(&inv)[n..], vec![0; len - n]);
+
+        plan.ifft(&src, &inv).unwrap();
+        assert_eq!(read(&inv), fft.ifft(&arr).unwrap());
+    }
+
+    #[rstest(
+        n,
+        m,
+        budget,
+        case(1, 1, 24),
+        case(1, 7, 80),
+        case(100, 50, 512),
+        case(1000, 1025, 1 << 12)
+    )]
+    fn test_out_of_core_convolve(n: usize, m: usize, budget: usize) {
+        const P: u64 = 998244353;
+        let mut rng = rng();
+        let dir = tempdir().unwrap();
+        let (a, b, dst) = (
+            dir.path().join("a"),
+            dir.path().join("b"),
+            dir.path().join("dst"),
+        );
+
+        let plan = OutOfCoreFFT::new(StaticFp::<P>, budget);
+        let fft = FFT(StaticFp::<P>);
+
+        let A: Vec<u64> = (0..n).map(|_| rng.random_range(0..P)).collect();
+        let B: Vec<u64> = (0..m).map(|_| rng.random_range(0..P)).collect();
+        write(&a, &A);
+        write(&b, &B);
+
+        assert_eq!(plan.convolve(&a, &b, &dst).unwrap(), n + m - 1);
+        assert_eq!(read(&dst), fft.convolve(&A, &B).unwrap());
+        // 作業用のファイルは削除される
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_out_of_core_error() {
+        let dir = tempdir().unwrap();
+        let (src, dst) = (dir.path().join("src"), dir.path().join("dst"));
+        let plan = OutOfCoreFFT::new(StaticFp::<998244353>, 64);
+
+        // 入力のファイルが存在しない
+        assert!(plan.fft(&src, &dst).is_err());
+
+        // 長さ 2^10 の変換には回転因子の表と 2^5 個の値の列で 8 * 96 バイトが必要
+        write(&src, &[1; 1 << 10]);
+        assert!(plan.fft(&src, &dst).is_err());
+        assert!(OutOfCoreFFT::new(StaticFp::<998244353>, 767)
+            .fft(&src, &dst)
+            .is_err());
+        assert!(OutOfCoreFFT::new(StaticFp::<998244353>, 768)
+            .fft(&src, &dst)
+            .is_ok());
+
+        // 2^4 を超える長さの変換はできない
+        write(&src, &[1; 17]);
+        assert!(OutOfCoreFFT::new(StaticFp::<17>, 1024)
+            .fft(&src, &dst)
+            .is_err());
+
+        // 8 バイトの倍数でない
+        fs::write(&src, [0; 12]).unwrap();
+        assert!(plan.fft(&src, &dst).is_err());
+
+        // 空の配列の畳み込みは空
+        write(&src, &[]);
+        write(&dst, &[1, 2, 3]);
+        assert_eq!(plan.convolve(&src, &src, &dst), Ok(0));
+        assert_eq!(read(&dst), vec![]);
+    }
+    #[test]
+    fn test_out_of_core_alias() {
+        let dir = tempdir().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        let plan = OutOfCoreFFT::new(StaticFp::<998244353>, 1 << 10);
+
+        write(&a, &[1, 2, 3, 4]);
+        write(&b, &[5, 6, 7]);
+
+        // 入力と出力が同じファイル
+        assert!(plan.fft(&a, &a).is_err());
+        assert!(plan.ifft(&a, dir.path().join(".").join("a")).is_err());
+        assert!(plan.convolve(&a, &b, &b).is_err());
+        assert!(plan.convolve(&a, &b, &a).is_err());
+        #[cfg(unix)]
+        {
+            // ハードリンク
+            let link = dir.path().join("link");
+            fs::hard_link(&a, &link).unwrap();
+            assert!(plan.fft(&a, &link).is_err());
+            fs::remove_file(&link).unwrap();
+        }
+        // 作業用のファイルが入力と同じ
+        let (c, tmp) = (dir.path().join("c"), dir.path().join("c.tmp"));
+        write(&tmp, &[8, 9]);
+        assert!(plan.convolve(&a, &tmp, &c).is_err());
+        assert!(plan.convolve(&tmp, &b, &c).is_err());
+        assert_eq!(read(&tmp), vec![8, 9]);
+
+        // 入力は変更されない
+        assert_eq!(read(&a), vec![1, 2, 3, 4]);
+        assert_eq!(read(&b), vec![5, 6, 7]);
+
+        // 同じファイルを両方の入力に用いることはできる
+        let dst = dir.path().join("dst");
+        assert_eq!(plan.convolve(&a, &a, &dst), Ok(7));
+        assert_eq!(read(&dst), vec![1, 4, 10, 20, 25, 24, 16]);
+    }
+}
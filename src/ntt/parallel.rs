@@ -0,0 +1,377 @@
+//! 複数のスレッドによる FFT の実装
+
+use std::{sync::Barrier, thread};
+
+use crate::num::{Field, Fp};
+
+use super::{fft::bit_reverse, FFT};
+
+/// 並列化を行う配列の長さの既定値
+const DEFAULT_THRESHOLD: usize = 1 << 15;
+
+/// 複数のスレッドで計算する FFT
+///
+/// 先頭（逆変換では末尾）の log T 段はバタフライ演算を分割して各スレッドで計算し，
+/// 残りの段は互いに独立な長さ n/T 程度の部分変換を各スレッドで計算する．
+/// スレッドは変換ごとに 1 度だけ起動し，各段の間は [`Barrier`] で待ち合わせる．
+/// 演算の内容は [`FFT`] と同じであり，結果も一致する．
+pub struct ParallelFFT<F: Field = Fp> {
+    /// 変換に用いる FFT
+    fft: FFT<F>,
+    /// スレッド数
+    threads: usize,
+    /// この長さ未満の配列は並列化せずに計算する
+    threshold: usize,
+}
+
+impl<F: Field> ParallelFFT<F> {
+    /// 並列 FFT を作成する
+    ///
+    /// **Arguments**
+    /// - `field`: 変換を行う体
+    /// - `threads`: 用いるスレッド数（1 以上）
+    pub fn new(field: F, threads: usize) -> Result<Self, &'static str> {
+        if threads == 0 {
+            return Err("The number of threads should be positive.");
+        }
+
+        Ok(Self {
+            fft: FFT(field),
+            threads,
+            threshold: DEFAULT_THRESHOLD,
+        })
+    }
+
+    /// 並列化を行う配列の長さの下限を設定する
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// スレッド数
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// 並列化を行う配列の長さの下限
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// 変換を行う体
+    pub fn field(&self) -> &F {
+        &self.fft.0
+    }
+}
+
+impl<F: Field + Sync> ParallelFFT<F>
+where
+    F::Elem: Send + Sync,
+{
+    /// 入力された配列をフーリエ変換する
+    pub fn fft(&self, X: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        let (i, mut X) = self.fft.extend_array(X)?;
+        let n = X.len();
+        let levels = self.levels(n);
+        if levels == 0 {
+            return self.fft.fft(&X);
+        }
+
+        let field = self.field();
+        let w = field.root_pow2(i)?;
+        let pw = self.fft.twiddles(n, w);
+
+        let mut phases: Vec<Phase<F::Elem>> = vec![];
+        phases.push(self.map_phase(n, |v| field.to_repr(v)));
+        for h in (0..levels).map(|l| n >> (l + 1)) {
+            phases.push(self.butterfly_phase(n, h, |L, R, j, stride| {
+                field.dif_butterflies(L, R, &pw[j * stride..], stride)
+            }));
+        }
+        phases.push(self.block_phase(n, levels, |block| {
+            self.fft.fft_core_step(block, &pw, 1 << levels)
+        }));
+        phases.push(Box::new(|X: Shared<F::Elem>, t: usize| {
+            if t == 0 {
+                // SAFETY: この段では先頭のスレッドのみが配列に触れる
+                bit_reverse(unsafe { X.slice(0, n) });
+            }
+        }));
+        phases.push(self.map_phase(n, |v| field.to_value(v)));
+
+        self.run(&mut X, &phases);
+        Ok(X)
+    }
+
+    /// 入力された配列をフーリエ逆変換する
+    pub fn ifft(&self, F: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        let (i, mut F) = self.fft.extend_array(F)?;
+        let n = F.len();
+        let levels = self.levels(n);
+        if levels == 0 {
+            return self.fft.ifft(&F);
+        }
+
+        let field = self.field();
+        let w = field.root_pow2(i)?;
+        let pw = self.fft.twiddles(n, field.inv_repr(w));
+        let inv_n = field.inv_repr(field.embed(n as u64));
+
+        let mut phases: Vec<Phase<F::Elem>> = vec![];
+        phases.push(self.map_phase(n, |v| field.to_repr(v)));
+        phases.push(Box::new(|X: Shared<F::Elem>, t: usize| {
+            if t == 0 {
+                // SAFETY: この段では先頭のスレッドのみが配列に触れる
+                bit_reverse(unsafe { X.slice(0, n) });
+            }
+        }));
+        phases.push(self.block_phase(n, levels, |block| {
+            self.fft.ifft_core_step(block, &pw, 1 << levels)
+        }));
+        for h in (0..levels).rev().map(|l| n >> (l + 1)) {
+            phases.push(self.butterfly_phase(n, h, |L, R, j, stride| {
+                field.dit_butterflies(L, R, &pw[j * stride..], stride)
+            }));
+        }
+        phases.push(self.map_phase(n, |v| field.to_value(field.mul_repr(v, inv_n))));
+
+        self.run(&mut F, &phases);
+        Ok(F)
+    }
+
+    /// 各要素に関数を適用する段
+    fn map_phase<'a>(
+        &self,
+        n: usize,
+        f: impl Fn(F::Elem) -> F::Elem + Sync + 'a,
+    ) -> Phase<'a, F::Elem> {
+        let (threads, chunk) = (self.threads, n.div_ceil(self.threads));
+        Box::new(move |X: Shared<F::Elem>, t: usize| {
+            let (start, end) = portion(n, chunk, t, threads);
+            // SAFETY: 各スレッドは互いに素な範囲 [start, end) のみに触れる
+            let chunk = unsafe { X.slice(start, end - start) };
+            chunk.iter_mut().for_each(|v| *v = f(*v));
+        })
+    }
+
+    /// 長さ 2h のブロックのバタフライ演算をスレッド数程度の作業に分割して計算する段
+    ///
+    /// - `f`: f(左半分, 右半分, ブロック内の先頭の位置, 回転因子の間隔)
+    fn butterfly_phase<'a>(
+        &self,
+        n: usize,
+        h: usize,
+        f: impl Fn(&mut [F::Elem], &mut [F::Elem], usize, usize) + Sync + 'a,
+    ) -> Phase<'a, F::Elem> {
+        let stride = n / (2 * h);
+        let piece = (n / 2).div_ceil(self.threads).min(h);
+
+        // 各作業の左半分の先頭，長さ，ブロック内の先頭の位置
+        let mut jobs = vec![];
+        for block in (0..n).step_by(2 * h) {
+            for j in (0..h).step_by(piece) {
+                jobs.push((block + j, piece.min(h - j), j));
+            }
+        }
+
+        let (threads, per) = (self.threads, jobs.len().div_ceil(self.threads));
+        Box::new(move |X: Shared<F::Elem>, t: usize| {
+            let (start, end) = portion(jobs.len(), per, t, threads);
+            for &(l, len, j) in &jobs[start..end] {
+                // SAFETY: 各作業の範囲 [l, l + len), [l + h, l + h + len) は互いに素である
+                let (L, R) = unsafe { (X.slice(l, len), X.slice(l + h, len)) };
+                f(L, R, j, stride);
+            }
+        })
+    }
+
+    /// 長さ n / 2^levels の独立なブロックを各スレッドで変換する段
+    fn block_phase<'a>(
+        &self,
+        n: usize,
+        levels: usize,
+        f: impl Fn(&mut [F::Elem]) + Sync + 'a,
+    ) -> Phase<'a, F::Elem> {
+        let (threads, len) = (self.threads, n >> levels);
+        let blocks: usize = 1 << levels;
+        let per = blocks.div_ceil(threads);
+        Box::new(move |X: Shared<F::Elem>, t: usize| {
+            let (start, end) = portion(blocks, per, t, threads);
+            for b in start..end {
+                // SAFETY: 各ブロックの範囲は互いに素である
+                f(unsafe { X.slice(b * len, len) });
+            }
+        })
+    }
+
+    /// スレッド数個のスレッドを起動し，各段を全てのスレッドで順に実行する
+    ///
+    /// 段の間では全てのスレッドが Barrier で待ち合わせるため，
+    /// 各段で互いに素な範囲に触れていれば前の段の結果を読むことができる．
+    fn run(&self, X: &mut [F::Elem], phases: &[Phase<F::Elem>]) {
+        let X = Shared::new(X);
+        let barrier = Barrier::new(self.threads);
+        let worker = |t: usize| {
+            for (i, phase) in phases.iter().enumerate() {
+                if i > 0 {
+                    barrier.wait();
+                }
+                phase(X, t);
+            }
+        };
+
+        thread::scope(|s| {
+            for t in 1..self.threads {
+                s.spawn(move || worker(t));
+            }
+            // 呼び出したスレッドも先頭のスレッドとして計算する
+            worker(0);
+        });
+    }
+
+    /// バタフライ演算を分割して計算する段数（0 の場合は並列化しない）
+    fn levels(&self, n: usize) -> usize {
+        if self.threads == 1 || n < self.threshold || n < 2 {
+            return 0;
+        }
+        // 2^levels >= threads となるような最小の levels
+        let levels = self.threads.next_power_of_two().trailing_zeros() as usize;
+        levels.min(n.trailing_zeros() as usize)
+    }
+}
+
+/// 全てのスレッドで実行する段（配列，スレッドの番号）
+type Phase<'a, E> = Box<dyn Fn(Shared<E>, usize) + Sync + 'a>;
+
+/// total 個の作業を per 個ずつ分けたときの t 番目のスレッドの範囲
+fn portion(total: usize, per: usize, t: usize, threads: usize) -> (usize, usize) {
+    debug_assert!(per * threads >= total);
+    ((t * per).min(total), ((t + 1) * per).min(total))
+}
+
+/// スレッド間で共有する配列
+///
+/// 各段で各スレッドが互いに素な範囲のみに触れることを前提として，
+/// 同じ配列の部分列を複数のスレッドに渡す．
+struct Shared<E> {
+    /// 先頭の要素
+    ptr: *mut E,
+    /// 配列の長さ
+    len: usize,
+}
+
+impl<E> Clone for Shared<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for Shared<E> {}
+
+// SAFETY: 要素を複数のスレッドから同時に変更しないことは各段が保証する
+unsafe impl<E: Send> Send for Shared<E> {}
+unsafe impl<E: Send> Sync for Shared<E> {}
+
+impl<E> Shared<E> {
+    /// 配列を共有する
+    fn new(X: &mut [E]) -> Self {
+        Self {
+            ptr: X.as_mut_ptr(),
+            len: X.len(),
+        }
+    }
+
+    /// [start, start + len) の部分列
+    ///
+    /// # Safety
+    /// 部分列を使う間，他のスレッドはこの範囲に触れてはならない．
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn slice(&self, start: usize, len: usize) -> &mut [E] {
+        assert!(start + len <= self.len);
+        std::slice::from_raw_parts_mut(self.ptr.add(start), len)
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::{
+        ntt::FFT,
+        num::{Complex, ComplexField, Fp},
+    };
+
+    use super::ParallelFFT;
+
+    #[rstest(
+        n,
+        p,
+        threads,
+        case(1, 998244353, 4),
+        case(2, 998244353, 4),
+        case(5, 17, 3),
+        case(16, 17, 16),
+        case(1000, 998244353, 2),
+        case(1024, 998244353, 3),
+        case(4096, 5767169, 8),
+        case(200000, 998244353, 4),
+        case(1 << 14, 18446744069414584321, 5)
+    )]
+    fn test_parallel(n: usize, p: u64, threads: usize) {
+        let mut rng = rng();
+        let fft = FFT(Fp::new(p).unwrap());
+        let par = ParallelFFT::new(Fp::new(p).unwrap(), threads)
+            .unwrap()
+            .with_threshold(0);
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+
+        let res = par.fft(&arr).unwrap();
+        assert_eq!(res, fft.fft(&arr).unwrap());
+        assert_eq!(par.ifft(&arr).unwrap(), fft.ifft(&arr).unwrap());
+        assert_eq!(&par.ifft(&res).unwrap()[..n], arr);
+    }
+
+    #[test]
+    fn test_parallel_complex() {
+        let mut rng = rng();
+        let fft = FFT(ComplexField);
+        let par = ParallelFFT::new(ComplexField, 4).unwrap().with_threshold(0);
+
+        let arr: Vec<Complex> = (0..1 << 12)
+            .map(|_| Complex::new(rng.random(), rng.random()))
+            .collect();
+
+        // 演算の順序が同じなので浮動小数点数でも完全に一致する
+        let res = par.fft(&arr).unwrap();
+        assert!(res == fft.fft(&arr).unwrap());
+        assert!(par.ifft(&res).unwrap() == fft.ifft(&res).unwrap());
+    }
+
+    #[test]
+    fn test_parallel_threshold() {
+        let par = ParallelFFT::new(Fp::new(998244353).unwrap(), 4).unwrap();
+        assert_eq!(par.threads(), 4);
+        assert_eq!(par.levels(1 << 10), 0);
+        assert_eq!(par.levels(1 << 20), 2);
+
+        let par = par.with_threshold(16);
+        assert_eq!(par.threshold(), 16);
+        assert_eq!(par.levels(8), 0);
+        assert_eq!(par.levels(16), 2);
+
+        let par = ParallelFFT::new(Fp::new(998244353).unwrap(), 1).unwrap();
+        assert_eq!(par.levels(1 << 20), 0);
+    }
+
+    #[test]
+    fn test_parallel_error() {
+        assert!(ParallelFFT::new(Fp::new(998244353).unwrap(), 0).is_err());
+
+        let par = ParallelFFT::new(Fp::new(17).unwrap(), 2).unwrap();
+        assert!(par.fft(&[1; 17]).is_err());
+        assert!(par.ifft(&[1; 17]).is_err());
+    }
+}
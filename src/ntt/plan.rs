@@ -0,0 +1,193 @@
+//! 回転因子を事前計算した FFT の実装
+
+use crate::num::{Field, Fp};
+
+use super::FFT;
+
+/// 長さを固定した FFT の計画
+///
+/// 回転因子の表とビット反転の添字を事前に計算しておき，
+/// 同じ長さの変換を繰り返し高速に行う．
+pub struct FFTPlan<F: Field = Fp> {
+    /// 変換に用いる FFT
+    fft: FFT<F>,
+    /// 変換の長さ
+    n: usize,
+    /// 回転因子の表
+    twiddles: Vec<F::Elem>,
+    /// 逆変換の回転因子の表
+    itwiddles: Vec<F::Elem>,
+    /// ビット反転の添字
+    rev: Vec<usize>,
+    /// n^(-1)
+    inv_n: F::Elem,
+}
+
+impl<F: Field> FFTPlan<F> {
+    /// 長さ n の変換の計画を作成する
+    ///
+    /// **Arguments**
+    /// - `field`: 変換を行う体
+    /// - `n`: 変換の長さ（2 べき）
+    pub fn new(field: F, n: usize) -> Result<Self, &'static str> {
+        if !n.is_power_of_two() {
+            return Err("The length of the array should be a power of 2.");
+        }
+
+        let w = field.root_pow2(n.trailing_zeros() as usize)?;
+        let winv = field.inv_repr(w);
+        let inv_n = field.inv_repr(field.embed(n as u64));
+
+        let fft = FFT(field);
+        let twiddles = fft.twiddles(n, w);
+        let itwiddles = fft.twiddles(n, winv);
+
+        let shift = usize::BITS - n.trailing_zeros();
+        let rev = (0..n)
+            .map(|i| if n == 1 { 0 } else { i.reverse_bits() >> shift })
+            .collect();
+
+        Ok(Self {
+            fft,
+            n,
+            twiddles,
+            itwiddles,
+            rev,
+            inv_n,
+        })
+    }
+
+    /// 変換の長さ
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// 変換を行う体
+    pub fn field(&self) -> &F {
+        &self.fft.0
+    }
+
+    /// 配列をその場でフーリエ変換する
+    pub fn forward(&self, X: &mut [F::Elem]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.forward_repr(X);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 配列をその場でフーリエ逆変換する
+    pub fn inverse(&self, X: &mut [F::Elem]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.inverse_repr(X);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 内部表現の配列をその場でフーリエ変換する
+    pub(crate) fn forward_repr(&self, X: &mut [F::Elem]) {
+        self.fft.fft_core(X, &self.twiddles);
+        self.permute(X);
+    }
+
+    /// 内部表現の配列をその場でフーリエ逆変換する
+    pub(crate) fn inverse_repr(&self, X: &mut [F::Elem]) {
+        let field = self.field();
+
+        self.permute(X);
+        self.fft.ifft_core(X, &self.itwiddles);
+        X.iter_mut()
+            .for_each(|v| *v = field.mul_repr(*v, self.inv_n));
+    }
+
+    /// ビット反転順に並べ替える
+    fn permute(&self, X: &mut [F::Elem]) {
+        for (i, &j) in self.rev.iter().enumerate() {
+            if i < j {
+                X.swap(i, j);
+            }
+        }
+    }
+
+    /// 配列の長さが計画と一致するか確認する
+    fn check_len(&self, X: &[F::Elem]) -> Result<(), &'static str> {
+        if X.len() != self.n {
+            return Err("The length of the array does not match the plan.");
+        }
+        Ok(())
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::{
+        ntt::FFT,
+        num::{Fp, StaticFp},
+    };
+
+    use super::FFTPlan;
+
+    #[rstest(
+        n,
+        p,
+        case(1, 5),
+        case(2, 5),
+        case(4, 5),
+        case(8, 17),
+        case(1024, 5767169),
+        case(1 << 15, 998244353)
+    )]
+    fn test_plan(n: usize, p: u64) {
+        let mut rng = rng();
+
+        let plan = FFTPlan::new(Fp::new(p).unwrap(), n).unwrap();
+        let fft = FFT(Fp::new(p).unwrap());
+        assert_eq!(plan.size(), n);
+
+        for _ in 0..3 {
+            let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+
+            let mut X = arr.clone();
+            plan.forward(&mut X).unwrap();
+            assert_eq!(X, fft.fft(&arr).unwrap());
+
+            plan.inverse(&mut X).unwrap();
+            assert_eq!(X, arr);
+        }
+    }
+
+    #[test]
+    fn test_plan_reuse() {
+        let plan = FFTPlan::new(StaticFp::<998244353>, 16).unwrap();
+
+        let mut X: Vec<u64> = (0..16).collect();
+        for _ in 0..10000 {
+            plan.forward(&mut X).unwrap();
+            plan.inverse(&mut X).unwrap();
+        }
+        assert_eq!(X, (0..16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_plan_error() {
+        assert!(FFTPlan::new(StaticFp::<998244353>, 0).is_err());
+        assert!(FFTPlan::new(StaticFp::<998244353>, 12).is_err());
+        assert!(FFTPlan::new(StaticFp::<998244353>, 1 << 24).is_err());
+        assert!(FFTPlan::new(StaticFp::<5>, 8).is_err());
+
+        let plan = FFTPlan::new(StaticFp::<998244353>, 8).unwrap();
+        assert!(plan.forward(&mut [1, 2, 3, 4]).is_err());
+        assert!(plan.inverse(&mut [0; 16]).is_err());
+    }
+}
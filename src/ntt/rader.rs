@@ -0,0 +1,277 @@
+//! Rader のアルゴリズムの実装
+
+use crate::num::{factorize, is_prime, Field, Fp, Modulus};
+
+use super::{convolution::CyclicConvolver, FFT};
+
+/// 素数長の NTT の計画（Rader のアルゴリズム）
+///
+/// p - 1 を割り切る素数 n に対して，(Z/nZ)* の生成元 g を用いて
+/// 長さ n の変換を長さ n - 1 の巡回畳み込みに帰着する．
+pub struct Rader<M: Modulus = Fp> {
+    /// 畳み込みに用いる FFT
+    fft: FFT<M>,
+    /// 変換の本体
+    core: RaderCore,
+    /// n^(-1)
+    inv_n: u64,
+}
+
+impl<M: Modulus> Rader<M> {
+    /// 長さ n の変換の計画を作成する
+    ///
+    /// **Arguments**
+    /// - `field`: 変換を行う体
+    /// - `n`: 変換の長さ（p - 1 を割り切る素数）
+    pub fn new(field: M, n: usize) -> Result<Self, &'static str> {
+        let fft = FFT(field);
+        let core = RaderCore::new(&fft, n)?;
+        let inv_n = fft.0.inv_repr(fft.0.embed(n as u64));
+
+        Ok(Self { fft, core, inv_n })
+    }
+
+    /// 変換の長さ
+    pub fn size(&self) -> usize {
+        self.core.n
+    }
+
+    /// 変換を行う体
+    pub fn field(&self) -> &M {
+        &self.fft.0
+    }
+
+    /// 配列をその場でフーリエ変換する
+    pub fn forward(&self, X: &mut [u64]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.forward_repr(X);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 配列をその場でフーリエ逆変換する
+    pub fn inverse(&self, X: &mut [u64]) -> Result<(), &'static str> {
+        self.check_len(X)?;
+        let field = self.field();
+
+        X.iter_mut().for_each(|v| *v = field.to_repr(*v));
+        self.inverse_repr(X);
+        X.iter_mut().for_each(|v| *v = field.to_value(*v));
+
+        Ok(())
+    }
+
+    /// 内部表現の配列をその場でフーリエ変換する
+    pub(crate) fn forward_repr(&self, X: &mut [u64]) {
+        self.core.apply(&self.fft, X, false);
+    }
+
+    /// 内部表現の配列をその場でフーリエ逆変換する
+    pub(crate) fn inverse_repr(&self, X: &mut [u64]) {
+        self.core.apply(&self.fft, X, true);
+
+        let field = self.field();
+        X.iter_mut()
+            .for_each(|v| *v = field.mul_repr(*v, self.inv_n));
+    }
+
+    /// 配列の長さが計画と一致するか確認する
+    fn check_len(&self, X: &[u64]) -> Result<(), &'static str> {
+        if X.len() != self.core.n {
+            return Err("The length of the array does not match the plan.");
+        }
+        Ok(())
+    }
+}
+
+/// Rader のアルゴリズムの本体
+///
+/// 体を保持せず，混合基数 NTT の基数としても用いる．
+/// 入出力は内部表現で与え，逆変換の正規化は行わない．
+pub(crate) struct RaderCore {
+    /// 変換の長さ（素数）
+    n: usize,
+    /// g^a mod n（a < n - 1）
+    perm: Vec<usize>,
+    /// g^(-b) mod n（b < n - 1）
+    iperm: Vec<usize>,
+    /// 長さ n - 1 の巡回畳み込みを計算する 2 べきの巡回畳み込み
+    conv: CyclicConvolver<u64>,
+    /// ω^(g^(-c)) を畳み込み用に変換したもの
+    kernel: Vec<u64>,
+    /// ω^(-g^(-c)) を畳み込み用に変換したもの
+    ikernel: Vec<u64>,
+}
+
+impl RaderCore {
+    /// 長さ n の変換の本体を作成する
+    pub(crate) fn new<M: Modulus>(fft: &FFT<M>, n: usize) -> Result<Self, &'static str> {
+        let field = &fft.0;
+        let w = field.to_repr(field.root_of_unity(n as u64)?);
+        if !is_prime(n as u64) {
+            return Err("The length of the transform should be prime.");
+        }
+
+        // g^a mod n（g は (Z/nZ)* の生成元）
+        let g = Fp::find_root(n as u64, &factorize(n as u64 - 1));
+        let perm: Vec<usize> = (0..n - 1)
+            .scan(1u64, |x, _| {
+                let res = *x;
+                *x = (*x as u128 * g as u128 % n as u128) as u64;
+                Some(res as usize)
+            })
+            .collect();
+        // g^(-b) = g^(n - 1 - b)
+        let iperm: Vec<usize> = (0..n - 1).map(|b| perm[(n - 1 - b) % (n - 1)]).collect();
+
+        // 長さ n - 1 の巡回畳み込みを，2 べきの長さの巡回畳み込みに埋め込む
+        let m = n - 1;
+        let len = if m.is_power_of_two() {
+            m
+        } else {
+            (2 * m - 1).next_power_of_two()
+        };
+        let conv = fft.cyclic_convolver(len)?;
+
+        let kernel_of = |w: u64| {
+            let v: Vec<u64> = iperm.iter().map(|&e| field.pow_repr(w, e as u64)).collect();
+
+            // b_c = v_c（c < m），b_{len - t} = v_{m - t}（0 < t < m）
+            let mut b = v.clone();
+            b.resize(len, 0);
+            if len > m {
+                for t in 1..m {
+                    b[len - t] = v[m - t];
+                }
+            }
+            conv.kernel(fft, &b)
+        };
+        let kernel = kernel_of(w);
+        let ikernel = kernel_of(field.inv_repr(w));
+
+        Ok(Self {
+            n,
+            perm,
+            iperm,
+            conv,
+            kernel,
+            ikernel,
+        })
+    }
+
+    /// 変換の長さ
+    pub(crate) fn size(&self) -> usize {
+        self.n
+    }
+
+    /// 内部表現の配列をその場で変換する
+    ///
+    /// X_{g^(-b)} = X_0 + Σ_a X_{g^a} ω^(g^(a - b)) を長さ n - 1 の巡回畳み込みで求める．
+    ///
+    /// - `inverse`: ω の代わりに ω^(-1) を用いるか（正規化は行わない）
+    pub(crate) fn apply<M: Modulus>(&self, fft: &FFT<M>, X: &mut [u64], inverse: bool) {
+        let field = &fft.0;
+        let kernel = if inverse { &self.ikernel } else { &self.kernel };
+
+        let x0 = X[0];
+        let sum = X
+            .iter()
+            .fold(field.zero(), |acc, &v| field.add_repr(acc, v));
+
+        let mut a = vec![0; self.conv.len()];
+        for (v, &j) in a.iter_mut().zip(&self.perm) {
+            *v = X[j];
+        }
+        self.conv.apply(fft, &mut a, kernel);
+
+        for (&v, &k) in a.iter().zip(&self.iperm) {
+            X[k] = field.add_repr(x0, v);
+        }
+        X[0] = sum;
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::{
+        ntt::{dft::dft_naive, Bluestein},
+        num::{Fp, StaticFp},
+    };
+
+    use super::Rader;
+
+    #[rstest(
+        n,
+        p,
+        case(2, 7681),
+        case(3, 7681),
+        case(5, 7681),
+        case(7, 998244353),
+        case(17, 998244353),
+        case(11, 5767169),
+        case(257, 18446744069414584321),
+        case(641, 1312769) // 641 * 2^11 + 1
+    )]
+    fn test_rader(n: usize, p: u64) {
+        let mut rng = rng();
+        let fp = Fp::new(p).unwrap();
+        let plan = Rader::new(&fp, n).unwrap();
+        assert_eq!(plan.size(), n);
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+
+        let mut X = arr.clone();
+        plan.forward(&mut X).unwrap();
+        assert_eq!(X, dft_naive(&fp, &arr));
+
+        plan.inverse(&mut X).unwrap();
+        assert_eq!(X, arr);
+    }
+
+    #[test]
+    fn test_rader_large() {
+        let mut rng = rng();
+        let p = 18446744069414584321;
+        let n = 65537;
+
+        let plan = Rader::new(StaticFp::<18446744069414584321>, n).unwrap();
+        let bluestein = Bluestein::new(StaticFp::<18446744069414584321>, n).unwrap();
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+
+        let mut X = arr.clone();
+        plan.forward(&mut X).unwrap();
+
+        let mut Y = arr.clone();
+        bluestein.forward(&mut Y).unwrap();
+        assert_eq!(X, Y);
+
+        plan.inverse(&mut X).unwrap();
+        assert_eq!(X, arr);
+    }
+
+    #[test]
+    fn test_rader_error() {
+        // 998244353 - 1 = 2^23 * 7 * 17
+        assert!(Rader::new(StaticFp::<998244353>, 0).is_err());
+        assert_eq!(
+            Rader::new(StaticFp::<998244353>, 3).err(),
+            Some("The order of the root of unity should divide p - 1.")
+        );
+        assert!(Rader::new(StaticFp::<998244353>, 119).is_err());
+        // 167 - 1 = 2 * 83 では長さ 256 の畳み込みができない
+        assert!(Rader::new(StaticFp::<167>, 83).is_err());
+
+        let plan = Rader::new(StaticFp::<998244353>, 7).unwrap();
+        assert!(plan.forward(&mut [1, 2, 3]).is_err());
+        assert!(plan.inverse(&mut [0; 8]).is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! 基数 4，分割基数の FFT の実装
+
+use crate::num::Field;
+
+use super::{fft::bit_reverse, FFT};
+
+/// FFT の本体に用いるバタフライ演算の種類
+///
+/// どれを選んでも結果は同じである．
+/// いずれも回転因子が 1 となる乗算を省き，長さ n = 2^k の変換での乗算の回数は
+/// (n/2) k - (n - 1) 回で等しい．
+///
+/// 複素数の場合と異なり，1 の原始 4 乗根を掛ける操作も 1 回の乗算となるため，
+/// 基数 4 と分割基数は NTT では乗算を減らさない．
+/// 基数 4 は配列を走査する回数が基数 2 の約半分になる．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Radix {
+    /// 基数 2（[`FFT::fft`] と同じ）
+    #[default]
+    Radix2,
+    /// 基数 4
+    Radix4,
+    /// 分割基数
+    SplitRadix,
+}
+
+impl<F: Field> FFT<F> {
+    /// 入力された配列を指定したバタフライ演算でフーリエ変換する
+    pub fn fft_with(&self, X: &[F::Elem], radix: Radix) -> Result<Vec<F::Elem>, &'static str> {
+        let X: Vec<F::Elem> = X.iter().map(|&v| self.0.to_repr(v)).collect();
+        let (i, mut X) = self.extend_array(&X)?;
+        let w = self.0.root_pow2(i)?;
+        let pw = self.twiddles(X.len(), w);
+
+        self.fft_core_with(&mut X, &pw, radix);
+        bit_reverse(&mut X);
+
+        Ok(X.into_iter().map(|v| self.0.to_value(v)).collect())
+    }
+
+    /// 入力された配列を指定したバタフライ演算でフーリエ逆変換する
+    pub fn ifft_with(&self, F: &[F::Elem], radix: Radix) -> Result<Vec<F::Elem>, &'static str> {
+        let F: Vec<F::Elem> = F.iter().map(|&v| self.0.to_repr(v)).collect();
+        let (i, mut F) = self.extend_array(&F)?;
+        let w = self.0.root_pow2(i)?;
+        let n = F.len();
+        let ipw = self.twiddles(n, self.0.inv_repr(w));
+
+        // 逆変換も周波数間引きで計算し，並べ替える
+        self.fft_core_with(&mut F, &ipw, radix);
+        bit_reverse(&mut F);
+
+        // 逆変換後の配列を正規化
+        let inv_n = self.0.inv_repr(self.0.embed(n as u64));
+        Ok(F.into_iter()
+            .map(|v| self.0.to_value(self.0.mul_repr(v, inv_n)))
+            .collect())
+    }
+
+    /// 指定したバタフライ演算で [`FFT::fft_core`] を計算する
+    ///
+    /// 入出力は内部表現で与え，結果はビット反転順で格納される．
+    pub(crate) fn fft_core_with(&self, X: &mut [F::Elem], pw: &[F::Elem], radix: Radix) {
+        match radix {
+            Radix::Radix2 => self.fft_core(X, pw),
+            Radix::Radix4 => self.fft_core_radix4(X, pw),
+            Radix::SplitRadix => self.fft_core_split_radix(X, pw, 1),
+        }
+    }
+
+    /// 基数 4 の周波数間引き FFT
+    ///
+    /// 基数 2 の 2 段分をまとめて計算し，log n が奇数の場合は最後に基数 2 の段を行う．
+    /// 各ブロックの先頭では回転因子が 1 となるため乗算を省く．
+    fn fft_core_radix4(&self, X: &mut [F::Elem], pw: &[F::Elem]) {
+        let n = X.len();
+        if n < 2 {
+            return;
+        }
+
+        // 1 の原始 4 乗根
+        let im = if n >= 4 { pw[n / 4] } else { self.0.one() };
+
+        let mut len = n;
+        while len >= 4 {
+            let q = len / 4;
+            let stride = n / len;
+            for block in X.chunks_exact_mut(len) {
+                for j in 0..q {
+                    let (x0, x1, x2, x3) =
+                        (block[j], block[j + q], block[j + 2 * q], block[j + 3 * q]);
+
+                    let (s0, d0) = (self.0.add_repr(x0, x2), self.0.sub_repr(x0, x2));
+                    let (s1, d1) = (
+                        self.0.add_repr(x1, x3),
+                        self.0.mul_repr(self.0.sub_repr(x1, x3), im),
+                    );
+
+                    block[j] = self.0.add_repr(s0, s1);
+                    block[j + q] = self.mul_twiddle(self.0.sub_repr(s0, s1), pw, 2 * j * stride);
+                    block[j + 2 * q] = self.mul_twiddle(self.0.add_repr(d0, d1), pw, j * stride);
+                    block[j + 3 * q] =
+                        self.mul_twiddle(self.0.sub_repr(d0, d1), pw, 3 * j * stride);
+                }
+            }
+            len /= 4;
+        }
+
+        // 残りの基数 2 の段
+        if len == 2 {
+            for block in X.chunks_exact_mut(2) {
+                let (a, b) = (block[0], block[1]);
+                block[0] = self.0.add_repr(a, b);
+                block[1] = self.0.sub_repr(a, b);
+            }
+        }
+    }
+
+    /// 分割基数の周波数間引き FFT
+    ///
+    /// 偶数番目の出力を長さ n/2 の変換で，4k+1, 4k+3 番目の出力を
+    /// 長さ n/4 の変換で再帰的に求める．先頭では回転因子が 1 となるため乗算を省く．
+    ///
+    /// - `step`: 回転因子の表を参照する間隔
+    fn fft_core_split_radix(&self, X: &mut [F::Elem], pw: &[F::Elem], step: usize) {
+        let n = X.len();
+        if n < 2 {
+            return;
+        }
+        if n == 2 {
+            let (a, b) = (X[0], X[1]);
+            X[0] = self.0.add_repr(a, b);
+            X[1] = self.0.sub_repr(a, b);
+            return;
+        }
+
+        // 1 の原始 4 乗根
+        let im = pw[n / 4 * step];
+
+        let q = n / 4;
+        for j in 0..q {
+            let (x0, x1, x2, x3) = (X[j], X[j + q], X[j + 2 * q], X[j + 3 * q]);
+
+            let d0 = self.0.sub_repr(x0, x2);
+            let d1 = self.0.mul_repr(self.0.sub_repr(x1, x3), im);
+
+            X[j] = self.0.add_repr(x0, x2);
+            X[j + q] = self.0.add_repr(x1, x3);
+            X[j + 2 * q] = self.mul_twiddle(self.0.add_repr(d0, d1), pw, j * step);
+            X[j + 3 * q] = self.mul_twiddle(self.0.sub_repr(d0, d1), pw, 3 * j * step);
+        }
+
+        let (L, R) = X.split_at_mut(2 * q);
+        let (R1, R3) = R.split_at_mut(q);
+        self.fft_core_split_radix(L, pw, 2 * step);
+        self.fft_core_split_radix(R1, pw, 4 * step);
+        self.fft_core_split_radix(R3, pw, 4 * step);
+    }
+
+    /// x * w^e（e = 0 の場合は乗算を行わない）
+    fn mul_twiddle(&self, x: F::Elem, pw: &[F::Elem], e: usize) -> F::Elem {
+        if e == 0 {
+            x
+        } else {
+            self.0.mul_repr(x, self.twiddle(pw, e))
+        }
+    }
+
+    /// w^e（w^(n/2) = -1 を用いて表の範囲外も参照する）
+    ///
+    /// - `pw`: 回転因子 w^0, ..., w^(n/2 - 1) の表
+    fn twiddle(&self, pw: &[F::Elem], e: usize) -> F::Elem {
+        let half = pw.len();
+        if e < half {
+            pw[e]
+        } else {
+            self.0.neg_repr(pw[e - half])
+        }
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::num::{Field, Fp, StaticFp};
+
+    use super::{super::FFT, Radix};
+
+    #[rstest(
+        n,
+        p,
+        case(1, 998244353),
+        case(2, 998244353),
+        case(4, 998244353),
+        case(8, 17),
+        case(16, 17),
+        case(7, 998244353),
+        case(512, 998244353),
+        case(1024, 998244353),
+        case(3000, 5767169),
+        case(1 << 15, 998244353),
+        case(1 << 12, 18446744069414584321)
+    )]
+    fn test_radix(n: usize, p: u64) {
+        let mut rng = rng();
+        let fft = FFT(Fp::new(p).unwrap());
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+        let expected = fft.fft(&arr).unwrap();
+        let expected_inv = fft.ifft(&arr).unwrap();
+
+        for radix in [Radix::Radix2, Radix::Radix4, Radix::SplitRadix] {
+            let res = fft.fft_with(&arr, radix).unwrap();
+            assert_eq!(res, expected, "{radix:?}");
+            assert_eq!(
+                fft.ifft_with(&arr, radix).unwrap(),
+                expected_inv,
+                "{radix:?}"
+            );
+            assert_eq!(&fft.ifft_with(&res, radix).unwrap()[..n], arr, "{radix:?}");
+        }
+    }
+
+    #[test]
+    fn test_radix_error() {
+        let fft = FFT(StaticFp::<17>);
+
+        assert!(fft.fft_with(&[1; 16], Radix::Radix4).is_ok());
+        assert!(fft.fft_with(&[1; 17], Radix::Radix4).is_err());
+        assert!(fft.ifft_with(&[1; 17], Radix::SplitRadix).is_err());
+        assert_eq!(Radix::default(), Radix::Radix2);
+    }
+
+    /// 乗算の回数を数える体
+    struct Counting(Fp, Cell<usize>);
+
+    impl Field for Counting {
+        type Elem = u64;
+
+        fn to_repr(&self, x: u64) -> u64 {
+            self.0.to_repr(x)
+        }
+
+        fn to_value(&self, x: u64) -> u64 {
+            self.0.to_value(x)
+        }
+
+        fn zero(&self) -> u64 {
+            self.0.zero()
+        }
+
+        fn one(&self) -> u64 {
+            self.0.one()
+        }
+
+        fn embed(&self, n: u64) -> u64 {
+            self.0.embed(n)
+        }
+
+        fn add_repr(&self, a: u64, b: u64) -> u64 {
+            self.0.add_repr(a, b)
+        }
+
+        fn sub_repr(&self, a: u64, b: u64) -> u64 {
+            self.0.sub_repr(a, b)
+        }
+
+        fn mul_repr(&self, a: u64, b: u64) -> u64 {
+            self.1.set(self.1.get() + 1);
+            self.0.mul_repr(a, b)
+        }
+
+        fn inv_repr(&self, a: u64) -> u64 {
+            self.0.inv_repr(a)
+        }
+
+        fn max_pow2(&self) -> usize {
+            self.0.max_pow2()
+        }
+
+        fn root_pow2(&self, a: usize) -> Result<u64, &'static str> {
+            self.0.root_pow2(a)
+        }
+    }
+
+    #[test]
+    fn test_radix_mul_count() {
+        let fft = FFT(Counting(Fp::new(998244353).unwrap(), Cell::new(0)));
+
+        // 基数 4 と分割基数でも乗算は減らない
+        for k in 0..=12 {
+            let n = 1 << k;
+            let pw = fft.twiddles(n, fft.0.root_pow2(k).unwrap());
+            let count = |radix| {
+                let mut X = vec![1; n];
+                fft.0 .1.set(0);
+                fft.fft_core_with(&mut X, &pw, radix);
+                fft.0 .1.get()
+            };
+
+            assert_eq!(count(Radix::Radix2), n / 2 * k - (n - 1), "{k}");
+            assert_eq!(count(Radix::Radix4), n / 2 * k - (n - 1), "{k}");
+            assert_eq!(count(Radix::SplitRadix), n / 2 * k - (n - 1), "{k}");
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! 切り詰めたフーリエ変換（TFT）の実装
+
+use crate::num::Field;
+
+use super::FFT;
+
+/// 順変換と逆変換の回転因子の表
+type TwiddlePair<E> = (Vec<E>, Vec<E>);
+
+impl<F: Field> FFT<F> {
+    /// 入力された配列を切り詰めたフーリエ変換（TFT）で変換する
+    ///
+    /// 長さ n の配列を 2^i >= n に拡張した変換のうち，必要な n 点だけを計算する．
+    /// 結果の j 番目は，長さ 2^i の変換結果をビット反転順に並べたときの j 番目である．
+    pub fn tft(&self, X: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        let n = X.len();
+        if n == 0 {
+            return Ok(vec![]);
+        }
+        let (pw, _) = self.tft_tables(n)?;
+
+        let mut X = self.tft_buffer(X);
+        self.tft_core(&mut X, n, n, &pw, 1);
+        X.truncate(n);
+
+        Ok(X.into_iter().map(|v| self.0.to_value(v)).collect())
+    }
+
+    /// [`FFT::tft`] の逆変換を計算する
+    pub fn itft(&self, F: &[F::Elem]) -> Result<Vec<F::Elem>, &'static str> {
+        let n = F.len();
+        if n == 0 {
+            return Ok(vec![]);
+        }
+        let (pw, ipw) = self.tft_tables(n)?;
+
+        let mut F = self.tft_buffer(F);
+        self.itft_core(&mut F, n, &pw, &ipw, 1);
+        F.truncate(n);
+
+        Ok(F.into_iter().map(|v| self.0.to_value(v)).collect())
+    }
+
+    /// 長さ n の TFT に用いる回転因子の表（順変換，逆変換）
+    pub(crate) fn tft_tables(&self, n: usize) -> Result<TwiddlePair<F::Elem>, &'static str> {
+        let len = n.next_power_of_two();
+        let w = self.0.root_pow2(len.trailing_zeros() as usize)?;
+
+        Ok((
+            self.twiddles(len, w),
+            self.twiddles(len, self.0.inv_repr(w)),
+        ))
+    }
+
+    /// 配列を内部表現に変換し，長さが 2 べきになるようにゼロ埋めする
+    fn tft_buffer(&self, X: &[F::Elem]) -> Vec<F::Elem> {
+        let mut res: Vec<F::Elem> = X.iter().map(|&v| self.0.to_repr(v)).collect();
+        res.resize(X.len().next_power_of_two(), self.0.zero());
+        res
+    }
+
+    /// TFT の本体（Gentleman–Sande 型）
+    ///
+    /// 先頭 `n_in` 個以外がゼロである配列を変換し，ビット反転順の結果のうち
+    /// 先頭 `n_out` 個を求める（それ以外の位置の値は不定）．
+    ///
+    /// - `pw`: 長さ `X.len() * step` の変換の回転因子の表
+    pub(crate) fn tft_core(
+        &self,
+        X: &mut [F::Elem],
+        n_in: usize,
+        n_out: usize,
+        pw: &[F::Elem],
+        step: usize,
+    ) {
+        let n = X.len();
+        if n_in == 0 || n_out == 0 {
+            return;
+        }
+        if n_out == n {
+            self.fft_core_step(X, pw, step);
+            return;
+        }
+
+        let h = n / 2;
+        let (L, R) = X.split_at_mut(h);
+
+        // 入力がゼロの位置と，出力が不要な右半分は計算しない
+        for j in 0..n_in.min(h) {
+            let (a, b) = (L[j], R[j]);
+            L[j] = self.0.add_repr(a, b);
+            if n_out > h {
+                R[j] = self.0.mul_repr(self.0.sub_repr(a, b), pw[j * step]);
+            }
+        }
+
+        self.tft_core(L, n_in.min(h), n_out.min(h), pw, 2 * step);
+        if n_out > h {
+            self.tft_core(R, n_in.min(h), n_out - h, pw, 2 * step);
+        }
+    }
+
+    /// 逆 TFT の本体
+    ///
+    /// 先頭 m 個に変換結果（ビット反転順），残りに既知の入力が格納された配列から，
+    /// 先頭 m 個の入力を求める．残りの位置の値は変更しない．
+    ///
+    /// - `pw`, `ipw`: 長さ `X.len() * step` の変換の回転因子の表（順変換，逆変換）
+    pub(crate) fn itft_core(
+        &self,
+        X: &mut [F::Elem],
+        m: usize,
+        pw: &[F::Elem],
+        ipw: &[F::Elem],
+        step: usize,
+    ) {
+        let n = X.len();
+        if m == 0 {
+            return;
+        }
+        if m == n {
+            self.ifft_core_step(X, ipw, step);
+
+            let inv_n = self.0.inv_repr(self.0.embed(n as u64));
+            X.iter_mut().for_each(|v| *v = self.0.mul_repr(*v, inv_n));
+            return;
+        }
+
+        let h = n / 2;
+        let inv2 = self.0.inv_repr(self.0.embed(2));
+        let (L, R) = X.split_at_mut(h);
+
+        if m >= h {
+            // 左半分はすべて既知なので l_j = x_j + x_{j+h} が求まる
+            self.itft_core(L, h, pw, ipw, 2 * step);
+
+            // x_{j+h} が既知の位置では x_j と r_j = (x_j - x_{j+h}) ω^j が求まる
+            let k = m - h;
+            for j in k..h {
+                let xb = R[j];
+                let xa = self.0.sub_repr(L[j], xb);
+                L[j] = xa;
+                R[j] = self.0.mul_repr(self.0.sub_repr(xa, xb), pw[j * step]);
+            }
+
+            self.itft_core(R, k, pw, ipw, 2 * step);
+
+            for j in 0..k {
+                let (l, r) = (L[j], self.0.mul_repr(R[j], ipw[j * step]));
+                L[j] = self.0.mul_repr(self.0.add_repr(l, r), inv2);
+                R[j] = self.0.mul_repr(self.0.sub_repr(l, r), inv2);
+            }
+            // 既知の入力 x_{j+h} = x_j - r_j ω^(-j) を元に戻す
+            for j in k..h {
+                R[j] = self.0.sub_repr(L[j], self.0.mul_repr(R[j], ipw[j * step]));
+            }
+        } else {
+            // 右半分はすべて既知の入力なので，左半分だけを解けばよい
+            for j in m..h {
+                L[j] = self.0.add_repr(L[j], R[j]);
+            }
+
+            self.itft_core(L, m, pw, ipw, 2 * step);
+
+            for j in 0..h {
+                L[j] = self.0.sub_repr(L[j], R[j]);
+            }
+        }
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::num::{Fp, StaticFp};
+
+    use super::super::FFT;
+
+    #[rstest(
+        n,
+        p,
+        case(1, 998244353),
+        case(2, 998244353),
+        case(3, 998244353),
+        case(5, 17),
+        case(16, 17),
+        case(100, 998244353),
+        case(1025, 998244353),
+        case(3000, 5767169),
+        case(1000, 18446744069414584321)
+    )]
+    fn test_tft(n: usize, p: u64) {
+        let mut rng = rng();
+        let fft = FFT(Fp::new(p).unwrap());
+
+        let arr: Vec<u64> = (0..n).map(|_| rng.random_range(0..p)).collect();
+        let res = fft.tft(&arr).unwrap();
+        assert_eq!(res.len(), n);
+
+        // 2 べきに拡張した変換のビット反転順の先頭 n 個と一致する
+        let full = fft.fft(&arr).unwrap();
+        let len = full.len();
+        for (j, &v) in res.iter().enumerate() {
+            let rev = if len == 1 {
+                0
+            } else {
+                j.reverse_bits() >> (usize::BITS - len.trailing_zeros())
+            };
+            assert_eq!(v, full[rev]);
+        }
+
+        assert_eq!(fft.itft(&res).unwrap(), arr);
+    }
+
+    #[test]
+    fn test_tft_empty_and_error() {
+        let fft = FFT(StaticFp::<17>);
+
+        assert_eq!(fft.tft(&[]), Ok(vec![]));
+        assert_eq!(fft.itft(&[]), Ok(vec![]));
+        assert!(fft.tft(&[1; 16]).is_ok());
+        assert!(fft.tft(&[1; 17]).is_err());
+        assert!(fft.itft(&[1; 17]).is_err());
+    }
+
+    #[rstest(n, case(1 << 10), case((1 << 10) + 1), case((1 << 11) - 1))]
+    fn test_tft_convolve(n: usize) {
+        // 長さが 2 べきをわずかに超える場合も畳み込みが正しく計算できる
+        const P: u64 = 998244353;
+        let mut rng = rng();
+        let fft = FFT(StaticFp::<P>);
+
+        let a: Vec<u64> = (0..n).map(|_| rng.random_range(0..P)).collect();
+        let b: Vec<u64> = (0..n / 2 + 1).map(|_| rng.random_range(0..P)).collect();
+
+        let res = fft.convolve(&a, &b).unwrap();
+        assert_eq!(res.len(), a.len() + b.len() - 1);
+
+        // 畳み込みの一部の係数を愚直に計算して比較する
+        for k in (0..res.len()).step_by(97).chain([res.len() - 1]) {
+            let expected = (0..a.len())
+                .filter(|&i| k >= i && k - i < b.len())
+                .fold(0u128, |acc, i| {
+                    (acc + a[i] as u128 * b[k - i] as u128) % P as u128
+                });
+            assert_eq!(res[k] as u128, expected);
+        }
+    }
+}
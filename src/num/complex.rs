@@ -0,0 +1,78 @@
+//! 複素数体の実装
+
+use std::f64::consts::PI;
+
+use super::Field;
+
+/// 複素数
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    /// 実部
+    pub re: f64,
+    /// 虚部
+    pub im: f64,
+}
+
+impl Complex {
+    /// 初期化する
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// 絶対値
+    pub fn abs(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+/// 倍精度浮動小数点数による複素数体
+///
+/// 1 の原始 2^a 乗根として exp(-2πi / 2^a) を用いる．
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComplexField;
+
+impl Field for ComplexField {
+    type Elem = Complex;
+
+    fn zero(&self) -> Complex {
+        Complex::new(0.0, 0.0)
+    }
+
+    fn one(&self) -> Complex {
+        Complex::new(1.0, 0.0)
+    }
+
+    fn embed(&self, n: u64) -> Complex {
+        Complex::new(n as f64, 0.0)
+    }
+
+    fn add_repr(&self, a: Complex, b: Complex) -> Complex {
+        Complex::new(a.re + b.re, a.im + b.im)
+    }
+
+    fn sub_repr(&self, a: Complex, b: Complex) -> Complex {
+        Complex::new(a.re - b.re, a.im - b.im)
+    }
+
+    fn mul_repr(&self, a: Complex, b: Complex) -> Complex {
+        Complex::new(a.re * b.re - a.im * b.im, a.re * b.im + a.im * b.re)
+    }
+
+    fn inv_repr(&self, a: Complex) -> Complex {
+        let norm = a.re * a.re + a.im * a.im;
+        Complex::new(a.re / norm, -a.im / norm)
+    }
+
+    fn max_pow2(&self) -> usize {
+        usize::BITS as usize - 1
+    }
+
+    fn root_pow2(&self, a: usize) -> Result<Complex, &'static str> {
+        if a > self.max_pow2() {
+            return Err("The length of the array is too large.");
+        }
+
+        let theta = -2.0 * PI / (1u64 << a) as f64;
+        Ok(Complex::new(theta.cos(), theta.sin()))
+    }
+}
@@ -0,0 +1,300 @@
+//! 中国剰余定理（Garner のアルゴリズム）の実装
+
+use super::Modulus;
+
+/// 中国剰余定理による復元
+///
+/// x ≡ r_i (mod m_i) となる 0 <= x < m_0 m_1 ... m_{k-1} を求める．
+/// 法の組に依存する定数を事前に計算しておき，復元を繰り返し行う．
+#[derive(Debug, Clone)]
+pub struct Garner {
+    /// 法 m_0, ..., m_{k-1}
+    moduli: Vec<u64>,
+    /// (m_0 ... m_{i-1})^(-1) mod m_i
+    inv: Vec<u64>,
+    /// prefix[i][j] = m_0 ... m_{j-1} mod m_i （j < i）
+    prefix: Vec<Vec<u64>>,
+    /// m_0 ... m_{k-1}（u128 に収まらない場合は None）
+    product: Option<u128>,
+}
+
+impl Garner {
+    /// 法の組から初期化する
+    ///
+    /// - `moduli`: 2 以上で，どの 2 つも互いに素な整数
+    pub fn new(moduli: &[u64]) -> Result<Self, &'static str> {
+        if moduli.iter().any(|&m| m < 2) {
+            return Err("Each modulus should be at least 2.");
+        }
+
+        let k = moduli.len();
+        let mut inv = Vec::with_capacity(k);
+        let mut prefix = Vec::with_capacity(k);
+
+        for (i, &mi) in moduli.iter().enumerate() {
+            let row: Vec<u64> = (0..i)
+                .scan(1, |acc, j| {
+                    let res = *acc;
+                    *acc = mul_mod(*acc, moduli[j], mi);
+                    Some(res)
+                })
+                .collect();
+            let all = row.last().map_or(1, |&v| mul_mod(v, moduli[i - 1], mi));
+
+            inv.push(inv_mod(all, mi).ok_or("The moduli should be pairwise coprime.")?);
+            prefix.push(row);
+        }
+
+        let product = moduli
+            .iter()
+            .try_fold(1u128, |acc, &m| acc.checked_mul(m as u128));
+
+        Ok(Self {
+            moduli: moduli.to_vec(),
+            inv,
+            prefix,
+            product,
+        })
+    }
+
+    /// [`Modulus`] の組から初期化する
+    pub fn from_moduli<M: Modulus>(moduli: &[M]) -> Result<Self, &'static str> {
+        Self::new(&moduli.iter().map(|m| m.modulus()).collect::<Vec<_>>())
+    }
+
+    /// 法の組
+    pub fn moduli(&self) -> &[u64] {
+        &self.moduli
+    }
+
+    /// 法の積（u128 に収まらない場合は None）
+    pub fn product(&self) -> Option<u128> {
+        self.product
+    }
+
+    /// x mod m を求める
+    ///
+    /// - `residues`: r_i （r_i < m_i でなくてもよい）
+    /// - `m`: 復元した値を還元する法（1 以上）
+    pub fn reconstruct_mod(&self, residues: &[u64], m: u64) -> u64 {
+        assert!(m >= 1, "`m` should be positive.");
+
+        let digits = self.digits(residues);
+
+        // x = t_0 + t_1 m_0 + t_2 m_0 m_1 + ...
+        let mut res = 0;
+        let mut coef = 1 % m;
+        for (&t, &mi) in digits.iter().zip(&self.moduli) {
+            res = add_mod(res, mul_mod(t % m, coef, m), m);
+            coef = mul_mod(coef, mi % m, m);
+        }
+        res
+    }
+
+    /// x を u128 で求める（法の積が u128 に収まらない場合で，x が u128 に収まらないときは None）
+    pub fn reconstruct_u128(&self, residues: &[u64]) -> Option<u128> {
+        let digits = self.digits(residues);
+
+        // Horner 法で x = t_0 + m_0 (t_1 + m_1 (t_2 + ...)) を計算する
+        digits
+            .iter()
+            .zip(&self.moduli)
+            .rev()
+            .try_fold(0u128, |acc, (&t, &mi)| {
+                acc.checked_mul(mi as u128)?.checked_add(t as u128)
+            })
+    }
+
+    /// x を u64 で求める（x が u64 に収まらない場合は None）
+    pub fn reconstruct_u64(&self, residues: &[u64]) -> Option<u64> {
+        self.reconstruct_u128(residues)?.try_into().ok()
+    }
+
+    /// -M/2 < x <= M/2 となるような符号付きの値を求める（M は法の積）
+    ///
+    /// 法の積が u128 に収まらない場合は None を返す．
+    pub fn reconstruct_signed(&self, residues: &[u64]) -> Option<i128> {
+        let product = self.product?;
+        let x = self.reconstruct_u128(residues)?;
+
+        if x <= product / 2 {
+            Some(x as i128)
+        } else {
+            Some(-((product - x) as i128))
+        }
+    }
+
+    /// x = t_0 + t_1 m_0 + t_2 m_0 m_1 + ... となるような t_i を求める
+    fn digits(&self, residues: &[u64]) -> Vec<u64> {
+        assert_eq!(
+            residues.len(),
+            self.moduli.len(),
+            "The number of residues should be equal to the number of moduli."
+        );
+
+        let mut digits: Vec<u64> = Vec::with_capacity(self.moduli.len());
+        for (i, (&r, &mi)) in residues.iter().zip(&self.moduli).enumerate() {
+            // t_0 + t_1 m_0 + ... + t_{i-1} m_0 ... m_{i-2} (mod m_i)
+            let partial = digits
+                .iter()
+                .zip(&self.prefix[i])
+                .fold(0, |acc, (&t, &c)| add_mod(acc, mul_mod(t % mi, c, mi), mi));
+
+            let t = mul_mod(sub_mod(r % mi, partial, mi), self.inv[i], mi);
+            digits.push(t);
+        }
+        digits
+    }
+}
+
+/// x ≡ r_i (mod m_i) となる 0 <= x < m_0 m_1 ... m_{k-1} を求める
+///
+/// **Arguments**
+/// - `residues`: r_i
+/// - `moduli`: 2 以上で，どの 2 つも互いに素な整数
+pub fn crt(residues: &[u64], moduli: &[u64]) -> Result<u128, &'static str> {
+    if residues.len() != moduli.len() {
+        return Err("The number of residues should be equal to the number of moduli.");
+    }
+    Garner::new(moduli)?
+        .reconstruct_u128(residues)
+        .ok_or("The product of the moduli does not fit in u128.")
+}
+
+/// a + b (mod m)
+fn add_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 + b as u128) % m as u128) as u64
+}
+
+/// a - b (mod m)
+fn sub_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 + m as u128 - b as u128) % m as u128) as u64
+}
+
+/// a * b (mod m)
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// a^(-1) (mod m)（存在しない場合は None）
+fn inv_mod(a: u64, m: u64) -> Option<u64> {
+    // 拡張ユークリッドの互除法
+    let (mut r0, mut r1) = (m as i128, (a % m) as i128);
+    let (mut s0, mut s1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    if r0 != 1 {
+        return None;
+    }
+    Some(s0.rem_euclid(m as i128) as u64)
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+
+    use crate::num::{Fp, StaticFp};
+
+    use super::{crt, Garner};
+
+    const PRIMES: [u64; 3] = [754974721, 167772161, 469762049];
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[2, 3, 2], &[3, 5, 7]), Ok(23));
+        assert_eq!(crt(&[1, 0], &[4, 9]), Ok(9));
+        assert_eq!(crt(&[], &[]), Ok(0));
+        assert!(crt(&[1, 1], &[4, 6]).is_err());
+        assert!(crt(&[1], &[1]).is_err());
+        assert!(crt(&[1, 2], &[3]).is_err());
+    }
+
+    #[test]
+    fn test_garner_random() {
+        let mut rng = rng();
+        let garner = Garner::new(&PRIMES).unwrap();
+        let product = garner.product().unwrap();
+
+        for _ in 0..1000 {
+            let x: u128 = rng.random_range(0..product);
+            let residues: Vec<u64> = PRIMES.iter().map(|&p| (x % p as u128) as u64).collect();
+
+            assert_eq!(garner.reconstruct_u128(&residues), Some(x));
+            assert_eq!(garner.reconstruct_u64(&residues), u64::try_from(x).ok());
+            assert_eq!(
+                garner.reconstruct_mod(&residues, 1000000007),
+                (x % 1000000007) as u64
+            );
+            // 2^63 を超える法では和が u64 に収まらない
+            assert_eq!(
+                garner.reconstruct_mod(&residues, 18446744069414584321),
+                (x % 18446744069414584321) as u64
+            );
+
+            let signed = if x <= product / 2 {
+                x as i128
+            } else {
+                x as i128 - product as i128
+            };
+            assert_eq!(garner.reconstruct_signed(&residues), Some(signed));
+        }
+    }
+
+    #[test]
+    fn test_garner_signed() {
+        let garner = Garner::new(&[3, 5, 7]).unwrap();
+
+        // -1 ≡ (2, 4, 6)
+        assert_eq!(garner.reconstruct_signed(&[2, 4, 6]), Some(-1));
+        assert_eq!(garner.reconstruct_signed(&[1, 1, 1]), Some(1));
+        assert_eq!(garner.reconstruct_signed(&[1, 2, 3]), Some(52));
+        assert_eq!(garner.reconstruct_signed(&[2, 3, 4]), Some(-52));
+    }
+
+    #[test]
+    fn test_garner_large() {
+        // 法の積が u128 に収まらない場合
+        let moduli = [
+            18446744069414584321,
+            4179340454199820289,
+            998244353,
+            1000000007,
+        ];
+        let garner = Garner::new(&moduli).unwrap();
+        assert_eq!(garner.product(), None);
+
+        let residues = [5, 5, 5, 5];
+        assert_eq!(garner.reconstruct_u128(&residues), Some(5));
+        assert_eq!(garner.reconstruct_mod(&residues, 17), 5);
+        assert_eq!(garner.reconstruct_signed(&residues), None);
+
+        // x = 2^130 + 3
+        let x_mod = |m: u64| {
+            let r = (1u128 << 127) % m as u128 * 8 % m as u128;
+            ((r + 3) % m as u128) as u64
+        };
+        let residues: Vec<u64> = moduli.iter().map(|&m| x_mod(m)).collect();
+        assert_eq!(garner.reconstruct_u128(&residues), None);
+        assert_eq!(garner.reconstruct_mod(&residues, 1 << 61), 3);
+        assert_eq!(garner.reconstruct_mod(&residues, 1000003), x_mod(1000003));
+        assert_eq!(
+            garner.reconstruct_mod(&residues, 18446744069414584321),
+            x_mod(18446744069414584321)
+        );
+        assert_eq!(garner.reconstruct_mod(&residues, u64::MAX), x_mod(u64::MAX));
+    }
+
+    #[test]
+    fn test_from_moduli() {
+        let fps = [Fp::new(998244353).unwrap(), Fp::new(1000000007).unwrap()];
+        let garner = Garner::from_moduli(&fps).unwrap();
+        assert_eq!(garner.moduli(), &[998244353, 1000000007]);
+
+        let garner = Garner::from_moduli(&[StaticFp::<5>, StaticFp::<5>]);
+        assert!(garner.is_err());
+    }
+}
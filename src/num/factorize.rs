@@ -0,0 +1,171 @@
+//! 素因数分解の実装
+
+use super::{is_prime, Montgomery};
+
+/// 素因数分解する
+///
+/// Miller–Rabin 法による素数判定と Pollard–Brent の rho 法を組み合わせる．
+///
+/// **Returns**
+/// - `(素因数, 指数)` の組を素因数の昇順に並べたもの
+pub fn factorize(mut n: u64) -> Vec<(u64, u64)> {
+    if n == 0 {
+        return vec![];
+    }
+
+    let mut primes = vec![];
+
+    // 小さい素因数は試し割りで取り除く
+    for p in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        while n.is_multiple_of(p) {
+            primes.push(p);
+            n /= p;
+        }
+    }
+
+    // 残りを rho 法で分解
+    let mut stack = vec![n];
+    while let Some(x) = stack.pop() {
+        if x == 1 {
+            continue;
+        }
+        if is_prime(x) {
+            primes.push(x);
+            continue;
+        }
+        let d = find_divisor(x);
+        stack.push(d);
+        stack.push(x / d);
+    }
+
+    primes.sort_unstable();
+
+    let mut res: Vec<(u64, u64)> = vec![];
+    for p in primes {
+        match res.last_mut() {
+            Some((q, cnt)) if *q == p => *cnt += 1,
+            _ => res.push((p, 1)),
+        }
+    }
+
+    res
+}
+
+/// 奇数の合成数 n の非自明な約数を 1 つ求める（Pollard–Brent の rho 法）
+fn find_divisor(n: u64) -> u64 {
+    /// gcd をまとめて計算する間隔
+    const M: u64 = 128;
+
+    let mont = Montgomery::new(n);
+
+    for c in 1.. {
+        let c = mont.to_mont(c);
+        // 擬似乱数列 f(x) = x^2 + c
+        let f = |x: u64| mont.add(mont.mul(x, x), c);
+
+        let (mut x, mut y, mut ys) = (0, mont.to_mont(2), 0);
+        let mut q = mont.one();
+        let (mut g, mut r) = (1, 1);
+
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..M.min(r - k) {
+                    y = f(y);
+                    q = mont.mul(q, x.abs_diff(y));
+                }
+                g = gcd(q, n);
+                k += M;
+            }
+
+            r <<= 1;
+        }
+
+        // まとめて計算した結果 n になってしまった場合は 1 つずつ戻って確認する
+        if g == n {
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g != 1 {
+                    break;
+                }
+            }
+        }
+
+        if g != n {
+            return g;
+        }
+    }
+
+    unreachable!()
+}
+
+/// 最大公約数
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::num::is_prime;
+
+    use super::factorize;
+
+    #[test]
+    fn test_factorize_small() {
+        assert_eq!(factorize(0), vec![]);
+        assert_eq!(factorize(1), vec![]);
+        assert_eq!(factorize(2), vec![(2, 1)]);
+        assert_eq!(factorize(12), vec![(2, 2), (3, 1)]);
+        assert_eq!(factorize(3628800), vec![(2, 8), (3, 4), (5, 2), (7, 1)]);
+        assert_eq!(factorize(1681), vec![(41, 2)]);
+    }
+
+    #[rstest(
+        n,
+        expected,
+        case(998244352, vec![(2, 23), (7, 1), (17, 1)]),
+        case(998244353 * 1000000007, vec![(998244353, 1), (1000000007, 1)]),
+        case(4294967291 * 4294967279, vec![(4294967279, 1), (4294967291, 1)]),
+        case(4294967291 * 4294967291, vec![(4294967291, 2)]),
+        case(18446744069414584320, vec![(2, 32), (3, 1), (5, 1), (17, 1), (257, 1), (65537, 1)]),
+        case(u64::MAX, vec![(3, 1), (5, 1), (17, 1), (257, 1), (641, 1), (65537, 1), (6700417, 1)]),
+        case(18446744073709551556, vec![(2, 2), (11, 1), (137, 1), (547, 1), (5594472617641, 1)]),
+    )]
+    fn test_factorize_large(n: u64, expected: Vec<(u64, u64)>) {
+        assert_eq!(factorize(n), expected);
+    }
+
+    #[test]
+    fn test_factorize_random() {
+        let mut rng = rng();
+
+        for _ in 0..200 {
+            let n: u64 = rng.random_range(1..=u64::MAX);
+            let factors = factorize(n);
+
+            assert!(factors.iter().all(|&(p, _)| is_prime(p)));
+            assert!(factors.windows(2).all(|w| w[0].0 < w[1].0));
+            assert_eq!(
+                factors
+                    .iter()
+                    .map(|&(p, e)| p.pow(e as u32) as u128)
+                    .product::<u128>(),
+                n as u128
+            );
+        }
+    }
+}
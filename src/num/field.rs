@@ -0,0 +1,340 @@
+//! 1 の冪根をもつ体の抽象化
+
+use super::{simd::Kernel, Modulus};
+
+/// 1 の 2 冪乗根をもつ体
+///
+/// FFT/DFT はこのトレイトを実装した型の上で計算される．
+/// 演算はすべて内部表現の上で行われ，入出力の際に
+/// [`Field::to_repr`], [`Field::to_value`] で変換する．
+///
+/// [`Modulus`] を実装した型（`Fp`, `StaticFp`）は Montgomery 表現を内部表現とする．
+/// 通常の表現で計算する `Fp::add` などと取り違えないよう，
+/// 内部表現の上の演算には `_repr` を付けた名前を用いる．
+pub trait Field {
+    /// 体の元
+    type Elem: Copy;
+
+    /// 通常の表現から内部表現に変換する
+    fn to_repr(&self, x: Self::Elem) -> Self::Elem {
+        x
+    }
+
+    /// 内部表現から通常の表現に変換する
+    fn to_value(&self, x: Self::Elem) -> Self::Elem {
+        x
+    }
+
+    /// 0
+    fn zero(&self) -> Self::Elem;
+
+    /// 1
+    fn one(&self) -> Self::Elem;
+
+    /// 整数 n に対応する元（内部表現）
+    fn embed(&self, n: u64) -> Self::Elem;
+
+    /// a + b
+    fn add_repr(&self, a: Self::Elem, b: Self::Elem) -> Self::Elem;
+
+    /// a - b
+    fn sub_repr(&self, a: Self::Elem, b: Self::Elem) -> Self::Elem;
+
+    /// a * b
+    fn mul_repr(&self, a: Self::Elem, b: Self::Elem) -> Self::Elem;
+
+    /// a^(-1)
+    fn inv_repr(&self, a: Self::Elem) -> Self::Elem;
+
+    /// -a
+    fn neg_repr(&self, a: Self::Elem) -> Self::Elem {
+        self.sub_repr(self.zero(), a)
+    }
+
+    /// a ^ b
+    fn pow_repr(&self, mut a: Self::Elem, mut b: u64) -> Self::Elem {
+        let mut res = self.one();
+        while b > 0 {
+            if b & 1 == 1 {
+                res = self.mul_repr(res, a);
+            }
+            a = self.mul_repr(a, a);
+            b >>= 1;
+        }
+        res
+    }
+
+    /// 1 の原始 2^a 乗根が存在するような最大の a
+    fn max_pow2(&self) -> usize;
+
+    /// 1 の原始 2^a 乗根
+    fn root_pow2(&self, a: usize) -> Result<Self::Elem, &'static str>;
+
+    /// 周波数間引きのバタフライ演算 (l_j, r_j) ← (l_j + r_j, (l_j - r_j) w_j) をまとめて行う
+    ///
+    /// - `pw`, `stride`: 回転因子 w_j = pw[j * stride]
+    fn dif_butterflies(
+        &self,
+        L: &mut [Self::Elem],
+        R: &mut [Self::Elem],
+        pw: &[Self::Elem],
+        stride: usize,
+    ) {
+        for ((l, r), &w) in L
+            .iter_mut()
+            .zip(R.iter_mut())
+            .zip(pw.iter().step_by(stride))
+        {
+            let (a, b) = (*l, *r);
+            *l = self.add_repr(a, b);
+            *r = self.mul_repr(self.sub_repr(a, b), w);
+        }
+    }
+
+    /// 時間間引きのバタフライ演算 (l_j, r_j) ← (l_j + r_j w_j, l_j - r_j w_j) をまとめて行う
+    ///
+    /// - `pw`, `stride`: 回転因子 w_j = pw[j * stride]
+    fn dit_butterflies(
+        &self,
+        L: &mut [Self::Elem],
+        R: &mut [Self::Elem],
+        pw: &[Self::Elem],
+        stride: usize,
+    ) {
+        for ((l, r), &w) in L
+            .iter_mut()
+            .zip(R.iter_mut())
+            .zip(pw.iter().step_by(stride))
+        {
+            let (a, b) = (*l, self.mul_repr(*r, w));
+            *l = self.add_repr(a, b);
+            *r = self.sub_repr(a, b);
+        }
+    }
+
+    /// 周波数間引きの FFT の全段のバタフライ演算を行う
+    ///
+    /// - `pw`: 長さ `X.len() * step` の変換の回転因子の表
+    fn dif_core(&self, X: &mut [Self::Elem], pw: &[Self::Elem], step: usize) {
+        dif_blocks(self, X, pw, step, |L, R, pw, stride| {
+            self.dif_butterflies(L, R, pw, stride)
+        });
+    }
+
+    /// 時間間引きの FFT の全段のバタフライ演算を行う
+    ///
+    /// - `pw`: 長さ `X.len() * step` の変換の回転因子の表
+    fn dit_core(&self, X: &mut [Self::Elem], pw: &[Self::Elem], step: usize) {
+        dit_blocks(self, X, pw, step, |L, R, pw, stride| {
+            self.dit_butterflies(L, R, pw, stride)
+        });
+    }
+}
+
+impl<M: Modulus> Field for M {
+    type Elem = u64;
+
+    #[inline]
+    fn to_repr(&self, x: u64) -> u64 {
+        self.mont().to_mont(x)
+    }
+
+    #[inline]
+    fn to_value(&self, x: u64) -> u64 {
+        self.mont().from_mont(x)
+    }
+
+    #[inline]
+    fn zero(&self) -> u64 {
+        0
+    }
+
+    #[inline]
+    fn one(&self) -> u64 {
+        self.mont().one()
+    }
+
+    #[inline]
+    fn embed(&self, n: u64) -> u64 {
+        self.mont().to_mont(n)
+    }
+
+    #[inline]
+    fn add_repr(&self, a: u64, b: u64) -> u64 {
+        self.mont().add(a, b)
+    }
+
+    #[inline]
+    fn sub_repr(&self, a: u64, b: u64) -> u64 {
+        self.mont().sub(a, b)
+    }
+
+    #[inline]
+    fn mul_repr(&self, a: u64, b: u64) -> u64 {
+        self.mont().mul(a, b)
+    }
+
+    #[inline]
+    fn inv_repr(&self, a: u64) -> u64 {
+        self.mont().inv(a)
+    }
+
+    #[inline]
+    fn neg_repr(&self, a: u64) -> u64 {
+        self.mont().neg(a)
+    }
+
+    #[inline]
+    fn pow_repr(&self, a: u64, b: u64) -> u64 {
+        self.mont().pow(a, b)
+    }
+
+    fn max_pow2(&self) -> usize {
+        self.k()
+    }
+
+    fn root_pow2(&self, a: usize) -> Result<u64, &'static str> {
+        Ok(self.mont().to_mont(self.root_pow2m(a)?))
+    }
+
+    /// p < 2^32 の場合は実行環境に応じて SIMD で計算する
+    fn dif_butterflies(&self, L: &mut [u64], R: &mut [u64], pw: &[u64], stride: usize) {
+        Kernel::new(self.mont()).dif(L, R, pw, stride);
+    }
+
+    /// p < 2^32 の場合は実行環境に応じて SIMD で計算する
+    fn dit_butterflies(&self, L: &mut [u64], R: &mut [u64], pw: &[u64], stride: usize) {
+        Kernel::new(self.mont()).dit(L, R, pw, stride);
+    }
+
+    /// 命令セットは変換ごとに 1 度だけ選択する
+    fn dif_core(&self, X: &mut [u64], pw: &[u64], step: usize) {
+        let kernel = Kernel::new(self.mont());
+        dif_blocks(self, X, pw, step, |L, R, pw, stride| {
+            kernel.dif(L, R, pw, stride)
+        });
+    }
+
+    /// 命令セットは変換ごとに 1 度だけ選択する
+    fn dit_core(&self, X: &mut [u64], pw: &[u64], step: usize) {
+        let kernel = Kernel::new(self.mont());
+        dit_blocks(self, X, pw, step, |L, R, pw, stride| {
+            kernel.dit(L, R, pw, stride)
+        });
+    }
+}
+
+/// 周波数間引きの各段の各ブロックのバタフライ演算を行う
+///
+/// 回転因子が 1 となる先頭のバタフライ演算は乗算を行わずに計算し，
+/// 残りについて f(左半分, 右半分, 回転因子の表, 回転因子の間隔) を呼ぶ．
+///
+/// - `step`: 回転因子の表を参照する間隔
+fn dif_blocks<F: Field + ?Sized>(
+    field: &F,
+    X: &mut [F::Elem],
+    pw: &[F::Elem],
+    step: usize,
+    mut f: impl FnMut(&mut [F::Elem], &mut [F::Elem], &[F::Elem], usize),
+) {
+    let n = X.len();
+
+    let mut h = n / 2;
+    while h >= 1 {
+        let stride = n / (2 * h) * step;
+        for block in X.chunks_exact_mut(2 * h) {
+            let (L, R) = block.split_at_mut(h);
+            let (a, b) = (L[0], R[0]);
+            L[0] = field.add_repr(a, b);
+            R[0] = field.sub_repr(a, b);
+            if h > 1 {
+                f(&mut L[1..], &mut R[1..], &pw[stride..], stride);
+            }
+        }
+        h /= 2;
+    }
+}
+
+/// 時間間引きの各段の各ブロックのバタフライ演算を行う
+///
+/// 回転因子が 1 となる先頭のバタフライ演算は乗算を行わずに計算し，
+/// 残りについて f(左半分, 右半分, 回転因子の表, 回転因子の間隔) を呼ぶ．
+///
+/// - `step`: 回転因子の表を参照する間隔
+fn dit_blocks<F: Field + ?Sized>(
+    field: &F,
+    X: &mut [F::Elem],
+    pw: &[F::Elem],
+    step: usize,
+    mut f: impl FnMut(&mut [F::Elem], &mut [F::Elem], &[F::Elem], usize),
+) {
+    let n = X.len();
+
+    let mut h = 1;
+    while h < n {
+        let stride = n / (2 * h) * step;
+        for block in X.chunks_exact_mut(2 * h) {
+            let (L, R) = block.split_at_mut(h);
+            let (a, b) = (L[0], R[0]);
+            L[0] = field.add_repr(a, b);
+            R[0] = field.sub_repr(a, b);
+            if h > 1 {
+                f(&mut L[1..], &mut R[1..], &pw[stride..], stride);
+            }
+        }
+        h *= 2;
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use crate::num::{Complex, ComplexField, Fp, StaticFp};
+
+    use super::Field;
+
+    #[test]
+    fn test_modulus_field() {
+        let fp = Fp::new(998244353).unwrap();
+        let sfp = StaticFp::<998244353>;
+
+        for x in [0, 1, 2, 998244352, 123456789] {
+            assert_eq!(fp.to_value(fp.to_repr(x)), x);
+            assert_eq!(sfp.to_repr(x), fp.to_repr(x));
+        }
+
+        let (a, b) = (fp.to_repr(3), fp.to_repr(998244352));
+        assert_eq!(fp.to_value(Field::add_repr(&fp, a, b)), 2);
+        assert_eq!(fp.to_value(Field::sub_repr(&fp, a, b)), 4);
+        assert_eq!(fp.to_value(Field::mul_repr(&fp, a, b)), 998244350);
+        assert_eq!(fp.to_value(Field::neg_repr(&fp, a)), 998244350);
+        assert_eq!(
+            fp.to_value(Field::mul_repr(&fp, a, Field::inv_repr(&fp, a))),
+            1
+        );
+
+        assert_eq!(Field::max_pow2(&fp), 23);
+        for a in 0..=23 {
+            let w = fp.root_pow2(a).unwrap();
+            assert_eq!(fp.to_value(w), fp.root_pow2m(a).unwrap());
+            assert_eq!(Field::pow_repr(&fp, w, 1 << a), Field::one(&fp));
+        }
+        assert!(fp.root_pow2(24).is_err());
+    }
+
+    #[test]
+    fn test_complex_field() {
+        let c = ComplexField;
+
+        let w = c.root_pow2(3).unwrap();
+        let w8 = c.pow_repr(w, 8);
+        assert!(c.sub_repr(w8, c.one()).abs() < 1e-12);
+
+        let w4 = c.pow_repr(w, 4);
+        assert!(c.add_repr(w4, c.one()).abs() < 1e-12);
+
+        let z = Complex::new(3.0, 4.0);
+        assert!(c.sub_repr(c.mul_repr(z, c.inv_repr(z)), c.one()).abs() < 1e-12);
+        assert_eq!(z.abs(), 5.0);
+    }
+}
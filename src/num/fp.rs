@@ -1,5 +1,7 @@
 //! 有限体の実装
 
+use super::{factorize, is_prime, Montgomery};
+
 /// 有限体の実装
 #[derive(Debug)]
 pub struct Fp {
@@ -13,13 +15,17 @@ pub struct Fp {
     pub k: usize,
     /// p = 2^k * m + 1 となるような m
     pub m: u64,
+    /// Montgomery 表現での演算
+    pub mont: Montgomery,
+    /// p - 1 の素因数分解
+    pub factors: Vec<(u64, u64)>,
 }
 
 impl Fp {
     /// 初期化する
     pub fn new(p: u64) -> Result<Self, &'static str> {
         // p は素数である必要がある
-        if Fp::factorize(p).len() > 1 {
+        if !is_prime(p) {
             return Err("`p` should be prime number.");
         }
 
@@ -38,11 +44,13 @@ impl Fp {
             rinv: Self::_inv(p, root),
             k,
             m: (p - 1) >> k,
+            mont: Montgomery::new(p),
+            factors,
         })
     }
 
     /// Fpの原始根を探索する
-    fn find_root(p: u64, factors: &Vec<(u64, u64)>) -> u64 {
+    pub(crate) fn find_root(p: u64, factors: &[(u64, u64)]) -> u64 {
         // x が Fp の原始根であるか判定する
         let is_ok = |x: u64| {
             factors
@@ -57,30 +65,8 @@ impl Fp {
     }
 
     /// 素因数分解
-    fn factorize(mut x: u64) -> Vec<(u64, u64)> {
-        let mut res = vec![];
-
-        for p in std::iter::once(2).chain((1..).map(|x| 2 * x + 1)) {
-            if p * p > x {
-                break;
-            }
-
-            let mut cnt = 0;
-            while x % p == 0 {
-                cnt += 1;
-                x /= p;
-            }
-
-            if cnt > 0 {
-                res.push((p, cnt));
-            }
-        }
-
-        if x > 1 {
-            res.push((x, 1));
-        }
-
-        res
+    fn factorize(x: u64) -> Vec<(u64, u64)> {
+        factorize(x)
     }
 
     // ===== 基本的な演算の実装 =====
@@ -97,18 +83,23 @@ impl Fp {
         let a = Self::normalize(p, a);
         let b = Self::normalize(p, b);
 
-        let mut res = a + b;
-        if res >= p {
-            res -= p;
+        // a + b >= p のとき a + b - p を返す（a + b のオーバーフローを避ける）
+        if a >= p - b {
+            a - (p - b)
+        } else {
+            a + b
         }
-        res
     }
 
     /// - a (mod p)
     fn _neg(p: u64, a: u64) -> u64 {
         let a = Self::normalize(p, a);
 
-        p - a
+        if a == 0 {
+            0
+        } else {
+            p - a
+        }
     }
 
     /// a - b (mod p)
@@ -124,7 +115,8 @@ impl Fp {
         let a = Self::normalize(p, a);
         let b = Self::normalize(p, b);
 
-        a * b % p
+        // 128 bit に拡張して計算する
+        (a as u128 * b as u128 % p as u128) as u64
     }
 
     /// a ^ b (mod p)
@@ -179,11 +171,48 @@ impl Fp {
 
         Ok(Self::_pow(self.p, self.root, self.m << (self.k - a)))
     }
+    /// 1 の原始 d 乗根 (mod p)
+    pub fn root_of_unity(&self, d: u64) -> Result<u64, &'static str> {
+        if d == 0 || !(self.p - 1).is_multiple_of(d) {
+            return Err("The order of the root of unity should divide p - 1.");
+        }
+
+        Ok(Self::_pow(self.p, self.root, (self.p - 1) / d))
+    }
+    /// 1 の原始 d 乗根 ω の冪 ω^0, ω^1, ..., ω^(d-1) (mod p)
+    pub fn root_of_unity_powers(&self, d: u64) -> Result<Vec<u64>, &'static str> {
+        let w = self.root_of_unity(d)?;
+
+        let mut x = 1;
+        Ok((0..d)
+            .map(|_| {
+                let res = x;
+                x = self.mul(x, w);
+                res
+            })
+            .collect())
+    }
+    /// a の位数（a ≠ 0）
+    ///
+    /// p - 1 の素因数分解を用いて，a^e = 1 となる最小の e >= 1 を求める．
+    pub fn order(&self, a: u64) -> u64 {
+        assert!(!a.is_multiple_of(self.p), "`a` should not be zero.");
+
+        let mut e = self.p - 1;
+        for &(q, _) in &self.factors {
+            while e.is_multiple_of(q) && Self::_pow(self.p, a, e / q) == 1 {
+                e /= q;
+            }
+        }
+        e
+    }
 }
 
 // ===== テスト =====
 #[cfg(test)]
 mod test {
+    use rstest::rstest;
+
     use super::Fp;
 
     const P: u64 = 998244353;
@@ -201,6 +230,103 @@ mod test {
         assert_eq!(Fp::factorize(1000000007), vec![(1000000007, 1)]);
     }
 
+    #[test]
+    fn test_new() {
+        assert!(Fp::new(0).is_err());
+        assert!(Fp::new(1).is_err());
+        assert!(Fp::new(998244353 * 1000000007).is_err());
+        assert!(Fp::new(3825123056546413051).is_err());
+
+        // 大きな素数でも素数判定は高速に行われる
+        let fp = Fp::new(18446744069414584321).unwrap();
+        assert_eq!((fp.k, fp.m), (32, 4294967295));
+
+        let fp = Fp::new(4179340454199820289).unwrap();
+        assert_eq!((fp.k, fp.m), (57, 29));
+    }
+
+    #[rstest(
+        p,
+        case(4294967311),
+        case(2305843009213693951),
+        case(4179340454199820289),
+        case(18446744069414584321),
+        case(18446744073709551557)
+    )]
+    fn test_new_large(p: u64) {
+        // p - 1 が大きな素因数をもつ場合も高速に初期化できる
+        let fp = Fp::new(p).unwrap();
+
+        assert_eq!(fp.pow(fp.root, (p - 1) as usize), 1);
+        assert_eq!(fp.mul(fp.root, fp.rinv), 1);
+        assert_eq!(((fp.m as u128) << fp.k) + 1, p as u128);
+
+        let w = fp.root_pow2m(fp.k).unwrap();
+        assert_eq!(fp.pow(w, 1 << fp.k), 1);
+        assert_ne!(fp.pow(w, 1 << (fp.k - 1)), 1);
+    }
+
+    #[rstest(
+        p,
+        case(5),
+        case(7681),
+        case(12289),
+        case(998244353),
+        case(18446744069414584321)
+    )]
+    fn test_root_of_unity(p: u64) {
+        let fp = Fp::new(p).unwrap();
+        assert_eq!(fp.order(fp.root), p - 1);
+
+        // p - 1 のすべての約数 d について原始 d 乗根を求める
+        let mut divisors = vec![1];
+        for &(q, e) in &fp.factors {
+            let mut next = vec![];
+            for &d in &divisors {
+                next.extend((0..=e as u32).map(|i| d * q.pow(i)));
+            }
+            divisors = next;
+        }
+        assert_eq!(
+            divisors.len() as u64,
+            fp.factors.iter().map(|&(_, e)| e + 1).product::<u64>()
+        );
+
+        for d in divisors {
+            let w = fp.root_of_unity(d).unwrap();
+            assert_eq!(fp.order(w), d);
+        }
+
+        assert_eq!(fp.root_of_unity(1 << fp.k), fp.root_pow2m(fp.k));
+        assert!(fp.root_of_unity(0).is_err());
+        assert!(fp.root_of_unity(p).is_err());
+        assert_eq!(
+            fp.root_of_unity(1 << (fp.k + 1)),
+            Err("The order of the root of unity should divide p - 1.")
+        );
+    }
+
+    #[test]
+    fn test_root_of_unity_powers() {
+        let fp = Fp::new(7681).unwrap();
+
+        let pw = fp.root_of_unity_powers(15).unwrap();
+        assert_eq!(pw.len(), 15);
+        assert_eq!(pw[0], 1);
+        assert_eq!(pw[1], fp.root_of_unity(15).unwrap());
+        for (i, &x) in pw.iter().enumerate() {
+            assert_eq!(fp.mul(x, pw[1]), pw[(i + 1) % 15]);
+        }
+
+        // 位数が異なる冪はすべて相異なる
+        let mut sorted = pw.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 15);
+
+        assert!(fp.root_of_unity_powers(7).is_err());
+    }
+
     #[test]
     fn test_add() {
         let fp = Fp::new(P).unwrap();
@@ -217,6 +343,52 @@ mod test {
         assert_eq!(fp.mul(2, P - 1), P - 2);
     }
 
+    #[rstest(
+        p,
+        case(5),
+        case(998244353),
+        case(4294967291), // 2^32 未満の最大の素数
+        case(4294967311), // 2^32 より大きい最小の素数
+        case(2305843009213693951), // 2^61 - 1
+        case(4179340454199820289), // 29 * 2^57 + 1
+        case(18446744069414584321), // 2^64 - 2^32 + 1
+        case(18446744073709551557), // 2^64 未満の最大の素数
+    )]
+    fn test_arith_large_prime(p: u64) {
+        let add = |a: u64, b: u64| ((a as u128 + b as u128) % p as u128) as u64;
+        let mul = |a: u64, b: u64| (a as u128 * b as u128 % p as u128) as u64;
+
+        let vals = [0, 1, 2, p / 2, p / 2 + 1, p - 2, p - 1];
+
+        for &a in &vals {
+            assert_eq!(Fp::_neg(p, a), (p - a) % p);
+            assert_eq!(Fp::_add(p, a, Fp::_neg(p, a)), 0);
+
+            for &b in &vals {
+                assert_eq!(Fp::_add(p, a, b), add(a, b));
+                assert_eq!(Fp::_sub(p, a, b), add(a, p - b));
+                assert_eq!(Fp::_mul(p, a, b), mul(a, b));
+            }
+
+            if a != 0 {
+                assert_eq!(Fp::_mul(p, a, Fp::_inv(p, a)), 1);
+            }
+        }
+
+        // フェルマーの小定理
+        assert_eq!(Fp::_pow(p, 3, p - 1), 1);
+        assert_eq!(Fp::_pow(p, p - 1, 2), 1);
+        // 正規化されていない入力
+        assert_eq!(
+            Fp::_add(p, u64::MAX, u64::MAX),
+            add(u64::MAX % p, u64::MAX % p)
+        );
+        assert_eq!(
+            Fp::_mul(p, u64::MAX, u64::MAX),
+            mul(u64::MAX % p, u64::MAX % p)
+        );
+    }
+
     #[test]
     fn test_pow() {
         let fp = Fp::new(P).unwrap();
@@ -255,6 +427,11 @@ mod test {
 
         let fpP = Fp::new(P).unwrap();
         assert_eq!(fpP.root, 3);
+
+        // Fp を作らずに探索する場合
+        assert_eq!(Fp::find_root(2, &[]), 1);
+        assert_eq!(Fp::find_root(3, &[(2, 1)]), 2);
+        assert_eq!(Fp::find_root(65537, &[(2, 16)]), 3);
     }
 
     #[test]
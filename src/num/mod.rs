@@ -1,3 +1,24 @@
+mod complex;
+mod crt;
+mod factorize;
+mod field;
 mod fp;
+mod modint;
+mod modulus;
+mod montgomery;
+mod ntt_prime;
+mod prime;
+mod simd;
+mod static_fp;
 
+pub use complex::{Complex, ComplexField};
+pub use crt::{crt, Garner};
+pub use factorize::factorize;
+pub use field::Field;
 pub use fp::Fp;
+pub use modint::Modint;
+pub use modulus::Modulus;
+pub use montgomery::Montgomery;
+pub use ntt_prime::{ntt_primes, plan_moduli, ModulusPlan, NttPrime, NTT_PRIMES};
+pub use prime::is_prime;
+pub use static_fp::StaticFp;
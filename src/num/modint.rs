@@ -0,0 +1,323 @@
+//! 有限体の元の実装
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::{Product, Sum},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+use super::Modulus;
+
+/// 法 `M` の有限体の元
+///
+/// 法には [`StaticFp`](super::StaticFp) または `&Fp` を用いる．
+/// 値は Montgomery 表現で保持する．
+#[derive(Clone, Copy)]
+pub struct Modint<M: Modulus + Copy> {
+    /// Montgomery 表現での値
+    raw: u64,
+    /// 法
+    modulus: M,
+}
+
+impl<M: Modulus + Copy> Modint<M> {
+    /// x (mod p) を生成する
+    pub fn new(modulus: M, x: u64) -> Self {
+        Self {
+            raw: modulus.mont().to_mont(x),
+            modulus,
+        }
+    }
+
+    /// Montgomery 表現の値から生成する
+    pub(crate) fn from_raw(modulus: M, raw: u64) -> Self {
+        Self { raw, modulus }
+    }
+
+    /// Montgomery 表現での値
+    pub(crate) fn raw(&self) -> u64 {
+        self.raw
+    }
+
+    /// 0 (mod p)
+    pub fn zero(modulus: M) -> Self {
+        Self::from_raw(modulus, 0)
+    }
+
+    /// 1 (mod p)
+    pub fn one(modulus: M) -> Self {
+        Self::from_raw(modulus, modulus.mont().one())
+    }
+
+    /// 0 <= x < p となるような値
+    pub fn value(&self) -> u64 {
+        self.modulus.mont().from_mont(self.raw)
+    }
+
+    /// 法
+    pub fn modulus(&self) -> M {
+        self.modulus
+    }
+
+    /// self ^ e (mod p)
+    pub fn pow(self, e: u64) -> Self {
+        Self::from_raw(self.modulus, self.modulus.mont().pow(self.raw, e))
+    }
+
+    /// self^(-1) (mod p)
+    ///
+    /// **Panics**
+    /// - `self` が 0 のとき
+    pub fn inv(self) -> Self {
+        assert!(self.raw != 0, "attempt to divide by zero");
+
+        Self::from_raw(self.modulus, self.modulus.mont().inv(self.raw))
+    }
+}
+
+// ===== 演算子の実装 =====
+impl<M: Modulus + Copy> Add for Modint<M> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        debug_assert_eq!(self.modulus.modulus(), rhs.modulus.modulus());
+        Self::from_raw(self.modulus, self.modulus.mont().add(self.raw, rhs.raw))
+    }
+}
+
+impl<M: Modulus + Copy> Sub for Modint<M> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        debug_assert_eq!(self.modulus.modulus(), rhs.modulus.modulus());
+        Self::from_raw(self.modulus, self.modulus.mont().sub(self.raw, rhs.raw))
+    }
+}
+
+impl<M: Modulus + Copy> Mul for Modint<M> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        debug_assert_eq!(self.modulus.modulus(), rhs.modulus.modulus());
+        Self::from_raw(self.modulus, self.modulus.mont().mul(self.raw, rhs.raw))
+    }
+}
+
+impl<M: Modulus + Copy> Div for Modint<M> {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inv()
+    }
+}
+
+impl<M: Modulus + Copy> Neg for Modint<M> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::from_raw(self.modulus, self.modulus.mont().neg(self.raw))
+    }
+}
+
+impl<M: Modulus + Copy> AddAssign for Modint<M> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<M: Modulus + Copy> SubAssign for Modint<M> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<M: Modulus + Copy> MulAssign for Modint<M> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<M: Modulus + Copy> DivAssign for Modint<M> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+// ===== 比較・表示 =====
+impl<M: Modulus + Copy> PartialEq for Modint<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.modulus.modulus() == other.modulus.modulus() && self.raw == other.raw
+    }
+}
+
+impl<M: Modulus + Copy> Eq for Modint<M> {}
+
+impl<M: Modulus + Copy> Hash for Modint<M> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+    }
+}
+
+impl<M: Modulus + Copy> fmt::Display for Modint<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+impl<M: Modulus + Copy> fmt::Debug for Modint<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+// ===== 法がコンパイル時に定まる場合 =====
+impl<M: Modulus + Copy + Default> From<u64> for Modint<M> {
+    fn from(x: u64) -> Self {
+        Self::new(M::default(), x)
+    }
+}
+
+impl<M: Modulus + Copy + Default> From<i64> for Modint<M> {
+    fn from(x: i64) -> Self {
+        let res = Self::from(x.unsigned_abs());
+        if x < 0 {
+            -res
+        } else {
+            res
+        }
+    }
+}
+
+impl<M: Modulus + Copy + Default> Sum for Modint<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(M::default()), Add::add)
+    }
+}
+
+impl<'a, M: Modulus + Copy + Default> Sum<&'a Modint<M>> for Modint<M> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl<M: Modulus + Copy + Default> Product for Modint<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::one(M::default()), Mul::mul)
+    }
+}
+
+impl<'a, M: Modulus + Copy + Default> Product<&'a Modint<M>> for Modint<M> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().product()
+    }
+}
+
+impl<M: Modulus + Copy + Default> FromStr for Modint<M> {
+    type Err = &'static str;
+
+    /// 10 進数表記の整数（負の数を含む）を mod p で解釈する
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (neg, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() {
+            return Err("cannot parse integer from empty string");
+        }
+
+        let ten = Self::from(10u64);
+        let res = digits
+            .bytes()
+            .try_fold(Self::zero(M::default()), |acc, c| {
+                if !c.is_ascii_digit() {
+                    return Err("invalid digit found in string");
+                }
+                Ok(acc * ten + Self::from((c - b'0') as u64))
+            })?;
+
+        Ok(if neg { -res } else { res })
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use crate::num::{Fp, StaticFp};
+
+    use super::Modint;
+
+    const P: u64 = 998244353;
+
+    type Mint = Modint<StaticFp<P>>;
+
+    #[test]
+    fn test_ops() {
+        let a = Mint::from(2u64);
+        let b = Mint::from(P - 1);
+
+        assert_eq!((a + b).value(), 1);
+        assert_eq!((a - b).value(), 3);
+        assert_eq!((a * b).value(), P - 2);
+        assert_eq!((a / b).value(), P - 2);
+        assert_eq!((-a).value(), P - 2);
+        assert_eq!((-Mint::from(0u64)).value(), 0);
+        assert_eq!(a.pow(10).value(), 1024);
+        assert_eq!((a * a.inv()).value(), 1);
+        assert_eq!(Mint::from(-1i64), b);
+
+        let mut c = a;
+        c += b;
+        assert_eq!(c.value(), 1);
+        c -= b;
+        assert_eq!(c, a);
+        c *= b;
+        assert_eq!(c.value(), P - 2);
+        c /= b;
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn test_div_by_zero() {
+        let _ = Mint::from(1u64) / Mint::from(P);
+    }
+
+    #[test]
+    fn test_sum_product() {
+        let arr: Vec<Mint> = (1..=10u64).map(Mint::from).collect();
+
+        assert_eq!(arr.iter().sum::<Mint>().value(), 55);
+        assert_eq!(arr.iter().product::<Mint>().value(), 3628800);
+        assert_eq!(arr.into_iter().rev().take(0).sum::<Mint>().value(), 0);
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        assert_eq!(Mint::from(12345u64).to_string(), "12345");
+        assert_eq!(format!("{:?}", Mint::from(P + 3)), "3");
+
+        assert_eq!("12345".parse::<Mint>(), Ok(Mint::from(12345u64)));
+        assert_eq!("+7".parse::<Mint>(), Ok(Mint::from(7u64)));
+        assert_eq!("-1".parse::<Mint>(), Ok(Mint::from(P - 1)));
+        assert_eq!(
+            "100000000000000000000".parse::<Mint>().map(|x| x.value()),
+            Ok((100000000000000000000u128 % P as u128) as u64)
+        );
+        assert!("".parse::<Mint>().is_err());
+        assert!("-".parse::<Mint>().is_err());
+        assert!("12a".parse::<Mint>().is_err());
+    }
+
+    #[test]
+    fn test_runtime_modulus() {
+        let fp = Fp::new(4179340454199820289).unwrap();
+        let p = fp.p;
+
+        let a = Modint::new(&fp, p - 1);
+        let b = Modint::new(&fp, 3);
+
+        assert_eq!((a + b).value(), 2);
+        assert_eq!((a * a).value(), 1);
+        assert_eq!((b / b).value(), 1);
+        assert_eq!(a.modulus().p, p);
+        assert_eq!(a.to_string(), (p - 1).to_string());
+    }
+}
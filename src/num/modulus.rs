@@ -0,0 +1,111 @@
+//! 法の共通インターフェース
+
+use super::{Fp, Montgomery};
+
+/// NTT に用いる素数 p を法とする演算の情報
+///
+/// 実行時に法を与える [`Fp`] と，コンパイル時に法を与える
+/// [`StaticFp`](super::StaticFp) を同様に扱うためのトレイト．
+pub trait Modulus {
+    /// mod p
+    fn modulus(&self) -> u64;
+
+    /// p の原始根
+    fn primitive_root(&self) -> u64;
+
+    /// p = 2^k * m + 1 となるような k
+    fn k(&self) -> usize;
+
+    /// Montgomery 表現での演算
+    fn mont(&self) -> Montgomery;
+
+    /// p = 2^k * m + 1 となるような m
+    fn m(&self) -> u64 {
+        (self.modulus() - 1) >> self.k()
+    }
+
+    /// 2^(1 / 2^a) (mod p)
+    fn root_pow2m(&self, a: usize) -> Result<u64, &'static str> {
+        if a > self.k() {
+            return Err("The prime p does not have enough factors of 2 in (p - 1).");
+        }
+
+        let mont = self.mont();
+        let root = mont.to_mont(self.primitive_root());
+
+        Ok(mont.from_mont(mont.pow(root, self.m() << (self.k() - a))))
+    }
+
+    /// 1 の原始 d 乗根 (mod p)
+    fn root_of_unity(&self, d: u64) -> Result<u64, &'static str> {
+        let p = self.modulus();
+        if d == 0 || !(p - 1).is_multiple_of(d) {
+            return Err("The order of the root of unity should divide p - 1.");
+        }
+
+        let mont = self.mont();
+        let root = mont.to_mont(self.primitive_root());
+
+        Ok(mont.from_mont(mont.pow(root, (p - 1) / d)))
+    }
+}
+
+impl Modulus for Fp {
+    fn modulus(&self) -> u64 {
+        self.p
+    }
+
+    fn primitive_root(&self) -> u64 {
+        self.root
+    }
+
+    fn k(&self) -> usize {
+        self.k
+    }
+
+    fn mont(&self) -> Montgomery {
+        self.mont
+    }
+
+    fn m(&self) -> u64 {
+        self.m
+    }
+
+    fn root_pow2m(&self, a: usize) -> Result<u64, &'static str> {
+        Fp::root_pow2m(self, a)
+    }
+
+    fn root_of_unity(&self, d: u64) -> Result<u64, &'static str> {
+        Fp::root_of_unity(self, d)
+    }
+}
+
+impl<M: Modulus + ?Sized> Modulus for &M {
+    fn modulus(&self) -> u64 {
+        (**self).modulus()
+    }
+
+    fn primitive_root(&self) -> u64 {
+        (**self).primitive_root()
+    }
+
+    fn k(&self) -> usize {
+        (**self).k()
+    }
+
+    fn mont(&self) -> Montgomery {
+        (**self).mont()
+    }
+
+    fn m(&self) -> u64 {
+        (**self).m()
+    }
+
+    fn root_pow2m(&self, a: usize) -> Result<u64, &'static str> {
+        (**self).root_pow2m(a)
+    }
+
+    fn root_of_unity(&self, d: u64) -> Result<u64, &'static str> {
+        (**self).root_of_unity(d)
+    }
+}
@@ -0,0 +1,213 @@
+//! Montgomery 乗算の実装
+
+/// Montgomery 表現による mod p の演算
+///
+/// R = 2^64 として，値 x を xR (mod p) の形で保持する．
+/// 乗算は除算を用いずに REDC によって行う．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Montgomery {
+    /// mod p（奇数）
+    pub p: u64,
+    /// p * pinv ≡ 1 (mod 2^64) となるような pinv
+    pub pinv: u64,
+    /// R^2 mod p
+    pub r2: u64,
+}
+
+impl Montgomery {
+    /// 初期化する
+    ///
+    /// - `p`: 奇数の法
+    pub const fn new(p: u64) -> Self {
+        assert!(p % 2 == 1, "`p` should be odd number.");
+
+        // Newton 法により p^(-1) mod 2^64 を求める
+        // （p * p ≡ 1 (mod 8) より，初期値 p は下位 3 bit が正しい）
+        let mut pinv = p;
+        let mut i = 0;
+        while i < 5 {
+            pinv = pinv.wrapping_mul(2u64.wrapping_sub(p.wrapping_mul(pinv)));
+            i += 1;
+        }
+
+        // R mod p, R^2 mod p
+        let r1 = ((1u128 << 64) % p as u128) as u64;
+        let r2 = (r1 as u128 * r1 as u128 % p as u128) as u64;
+
+        Self { p, pinv, r2 }
+    }
+
+    /// T * R^(-1) (mod p) を計算する（REDC）
+    ///
+    /// - `t`: 0 <= t < pR を満たす整数
+    #[inline]
+    pub const fn reduce(&self, t: u128) -> u64 {
+        let m = (t as u64).wrapping_mul(self.pinv);
+        let mp = m as u128 * self.p as u128;
+        // t と mp の下位 64 bit は一致するため，上位 64 bit の差をとればよい
+        let (t_hi, mp_hi) = ((t >> 64) as u64, (mp >> 64) as u64);
+        if t_hi < mp_hi {
+            t_hi.wrapping_sub(mp_hi).wrapping_add(self.p)
+        } else {
+            t_hi - mp_hi
+        }
+    }
+
+    /// 通常の表現から Montgomery 表現に変換する
+    #[inline]
+    pub const fn to_mont(&self, a: u64) -> u64 {
+        self.reduce((a % self.p) as u128 * self.r2 as u128)
+    }
+
+    /// Montgomery 表現から通常の表現に変換する
+    #[inline]
+    pub const fn from_mont(&self, a: u64) -> u64 {
+        self.reduce(a as u128)
+    }
+
+    /// Montgomery 表現での 1
+    #[inline]
+    pub const fn one(&self) -> u64 {
+        self.to_mont(1)
+    }
+
+    /// a + b (mod p)
+    #[inline]
+    pub const fn add(&self, a: u64, b: u64) -> u64 {
+        let (res, overflow) = a.overflowing_add(b);
+        if overflow || res >= self.p {
+            res.wrapping_sub(self.p)
+        } else {
+            res
+        }
+    }
+
+    /// -a (mod p)
+    #[inline]
+    pub const fn neg(&self, a: u64) -> u64 {
+        if a == 0 {
+            0
+        } else {
+            self.p - a
+        }
+    }
+
+    /// a - b (mod p)
+    #[inline]
+    pub const fn sub(&self, a: u64, b: u64) -> u64 {
+        if a >= b {
+            a - b
+        } else {
+            a.wrapping_sub(b).wrapping_add(self.p)
+        }
+    }
+
+    /// a * b (mod p)
+    #[inline]
+    pub const fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    /// a ^ b (mod p)
+    pub const fn pow(&self, mut a: u64, mut b: u64) -> u64 {
+        let mut res = self.one();
+        while b > 0 {
+            if b & 1 == 1 {
+                res = self.mul(res, a);
+            }
+            a = self.mul(a, a);
+            b >>= 1;
+        }
+        res
+    }
+
+    /// a^(-1) (mod p)
+    pub const fn inv(&self, a: u64) -> u64 {
+        self.pow(a, self.p - 2)
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::Montgomery;
+
+    const P: u64 = 998244353;
+
+    #[test]
+    fn test_new() {
+        let mont = Montgomery::new(P);
+
+        assert_eq!(mont.p.wrapping_mul(mont.pinv), 1);
+        assert_eq!(
+            mont.r2 as u128,
+            (1u128 << 64) % P as u128 * ((1u128 << 64) % P as u128) % P as u128
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mont = Montgomery::new(P);
+
+        for x in (0..=10).chain(P - 10..P) {
+            assert_eq!(mont.from_mont(mont.to_mont(x)), x);
+        }
+        assert_eq!(mont.from_mont(mont.to_mont(P)), 0);
+        assert_eq!(mont.from_mont(mont.one()), 1);
+    }
+
+    #[test]
+    fn test_ops() {
+        let mont = Montgomery::new(P);
+
+        for (a, b) in [
+            (2, 10),
+            (2, P - 1),
+            (P - 1, P - 1),
+            (0, 5),
+            (123456789, 987654321),
+        ] {
+            let (ma, mb) = (mont.to_mont(a), mont.to_mont(b));
+
+            assert_eq!(mont.from_mont(mont.add(ma, mb)), (a + b) % P);
+            assert_eq!(mont.from_mont(mont.sub(ma, mb)), (a + P - b) % P);
+            assert_eq!(mont.from_mont(mont.neg(ma)), (P - a) % P);
+            assert_eq!(mont.from_mont(mont.mul(ma, mb)), a * b % P);
+        }
+
+        let m2 = mont.to_mont(2);
+        assert_eq!(mont.from_mont(mont.pow(m2, 10)), 1024);
+        assert_eq!(mont.from_mont(mont.mul(m2, mont.inv(m2))), 1);
+    }
+
+    #[rstest(
+        p,
+        case(3),
+        case(4294967291),
+        case(4179340454199820289),
+        case(18446744069414584321),
+        case(18446744073709551557)
+    )]
+    fn test_ops_large_prime(p: u64) {
+        let mont = Montgomery::new(p);
+        let add = |a: u64, b: u64| ((a as u128 + b as u128) % p as u128) as u64;
+        let mul = |a: u64, b: u64| (a as u128 * b as u128 % p as u128) as u64;
+
+        let vals = [0, 1, 2, p / 2, p - 2, p - 1];
+
+        for &a in &vals {
+            let ma = mont.to_mont(a);
+            assert_eq!(mont.from_mont(ma), a);
+
+            for &b in &vals {
+                let mb = mont.to_mont(b);
+
+                assert_eq!(mont.from_mont(mont.add(ma, mb)), add(a, b));
+                assert_eq!(mont.from_mont(mont.sub(ma, mb)), add(a, p - b));
+                assert_eq!(mont.from_mont(mont.mul(ma, mb)), mul(a, b));
+            }
+        }
+    }
+}
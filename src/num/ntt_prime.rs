@@ -0,0 +1,331 @@
+//! NTT に適した素数の探索
+
+use super::{is_prime, Fp, StaticFp};
+
+/// NTT に適した素数 p = c * 2^k + 1（c は奇数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NttPrime {
+    /// 素数 p
+    pub p: u64,
+    /// p = c * 2^k + 1 となるような奇数 c
+    pub c: u64,
+    /// p = c * 2^k + 1 となるような k
+    pub k: usize,
+    /// p の最小の原始根
+    pub root: u64,
+}
+
+impl NttPrime {
+    /// 素数 p の情報を求める
+    pub fn new(p: u64) -> Result<Self, &'static str> {
+        let fp = Fp::new(p)?;
+
+        Ok(Self {
+            p,
+            c: fp.m,
+            k: fp.k,
+            root: fp.root,
+        })
+    }
+
+    /// コンパイル時に素数 `P` の情報を求める
+    pub const fn of<const P: u64>() -> Self {
+        Self {
+            p: StaticFp::<P>::MOD,
+            c: StaticFp::<P>::M,
+            k: StaticFp::<P>::K,
+            root: StaticFp::<P>::ROOT,
+        }
+    }
+
+    /// 実行時に法を与える有限体
+    pub fn to_fp(&self) -> Fp {
+        Fp::new(self.p).unwrap()
+    }
+}
+
+/// よく知られた NTT 素数
+pub const NTT_PRIMES: [NttPrime; 5] = [
+    // 119 * 2^23 + 1
+    NttPrime::of::<998244353>(),
+    // 7 * 2^26 + 1
+    NttPrime::of::<469762049>(),
+    // 5 * 2^25 + 1
+    NttPrime::of::<167772161>(),
+    // 7 * 2^20 + 1
+    NttPrime::of::<7340033>(),
+    // Goldilocks 素数 2^64 - 2^32 + 1 = (2^32 - 1) * 2^32 + 1
+    NttPrime::of::<18446744069414584321>(),
+];
+
+/// p = c * 2^k + 1 の形の素数を昇順に列挙する
+///
+/// **Arguments**
+/// - `min_bits`, `max_bits`: p のビット長の範囲（2 <= min_bits <= max_bits <= 64）
+/// - `min_k`: k の下限（1 以上）
+pub fn ntt_primes(
+    min_bits: u32,
+    max_bits: u32,
+    min_k: usize,
+) -> Result<impl DoubleEndedIterator<Item = NttPrime>, &'static str> {
+    if !(2 <= min_bits && min_bits <= max_bits && max_bits <= 64) {
+        return Err("The range of bits should satisfy 2 <= min_bits <= max_bits <= 64.");
+    }
+    if min_k == 0 {
+        return Err("`min_k` should be positive.");
+    }
+
+    // p - 1 = t * 2^min_k の t の範囲
+    let lo = 1u64 << (min_bits - 1);
+    let hi = u64::MAX >> (64 - max_bits);
+    let (t_lo, t_hi) = if min_k < 64 {
+        (((lo - 1) >> min_k).max(1), (hi - 1) >> min_k)
+    } else {
+        (1, 0)
+    };
+
+    Ok((t_lo..=t_hi)
+        .map(move |t| (t << min_k) + 1)
+        .filter(move |&p| lo <= p && is_prime(p))
+        .map(|p| NttPrime::new(p).unwrap()))
+}
+
+/// 畳み込みに用いる法の選択結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModulusPlan {
+    /// 1 つの素数で正確に計算できる
+    Single(NttPrime),
+    /// 複数の素数で計算し，中国剰余定理で復元する
+    Crt(Vec<NttPrime>),
+}
+
+impl ModulusPlan {
+    /// 用いる素数の一覧
+    pub fn primes(&self) -> &[NttPrime] {
+        match self {
+            Self::Single(prime) => std::slice::from_ref(prime),
+            Self::Crt(primes) => primes,
+        }
+    }
+}
+
+/// 変換長と係数の上限から，整数の畳み込みを正確に計算できる法を選択する
+///
+/// 係数 0 <= a_i < `bound_a`, 0 <= b_j < `bound_b` の配列の畳み込みを
+/// 長さ `max_len` 以下の変換で計算するとき，結果の各係数は
+/// `max_len * (bound_a - 1) * (bound_b - 1)` 以下となる．
+/// これを超える素数（またはその積が超える素数の組）を返す．
+///
+/// 単一の素数で足りる場合はよく知られた素数を優先して用いる．
+pub fn plan_moduli(
+    max_len: usize,
+    bound_a: u64,
+    bound_b: u64,
+) -> Result<ModulusPlan, &'static str> {
+    if max_len == 0 {
+        return Err("`max_len` should be positive.");
+    }
+
+    // 必要な 2 の指数
+    let k = max_len.next_power_of_two().trailing_zeros() as usize;
+
+    // 畳み込みの係数の上限
+    let max_coef = (max_len as u128)
+        .checked_mul(bound_a.saturating_sub(1) as u128)
+        .and_then(|v| v.checked_mul(bound_b.saturating_sub(1) as u128))
+        .ok_or("The coefficients of the convolution may not fit in u128.")?;
+
+    // よく知られた素数で足りる場合
+    if let Some(prime) = NTT_PRIMES
+        .iter()
+        .filter(|q| q.k >= k && q.p as u128 > max_coef)
+        .min_by_key(|q| q.p)
+    {
+        return Ok(ModulusPlan::Single(*prime));
+    }
+
+    // 64 bit の素数を大きい順に用いる
+    let mut candidates = ntt_primes(64, 64, k.max(1))?.rev();
+
+    let first = candidates
+        .next()
+        .ok_or("No prime with enough factors of 2 was found.")?;
+    if first.p as u128 > max_coef {
+        return Ok(ModulusPlan::Single(first));
+    }
+
+    // 最大の素数でも足りない場合は CRT を用いる
+    let mut primes = vec![first];
+    let mut product = Some(first.p as u128);
+    while product.is_some_and(|v| v <= max_coef) {
+        let prime = candidates
+            .next()
+            .ok_or("No prime with enough factors of 2 was found.")?;
+        product = product.and_then(|v| v.checked_mul(prime.p as u128));
+        primes.push(prime);
+    }
+
+    Ok(ModulusPlan::Crt(primes))
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use crate::{
+        ntt::FFT,
+        num::{Fp, Garner},
+    };
+
+    use super::{ntt_primes, plan_moduli, ModulusPlan, NttPrime, NTT_PRIMES};
+
+    #[test]
+    fn test_catalogue() {
+        for prime in NTT_PRIMES {
+            let fp = Fp::new(prime.p).unwrap();
+
+            assert_eq!(((prime.c as u128) << prime.k) + 1, prime.p as u128);
+            assert_eq!(prime.c % 2, 1);
+            assert_eq!((prime.k, prime.root), (fp.k, fp.root));
+            assert_eq!(NttPrime::new(prime.p), Ok(prime));
+        }
+
+        assert_eq!(
+            NTT_PRIMES[0],
+            NttPrime {
+                p: 998244353,
+                c: 119,
+                k: 23,
+                root: 3
+            }
+        );
+        assert_eq!(NTT_PRIMES[4].k, 32);
+        assert_eq!(NTT_PRIMES[4].root, 7);
+    }
+
+    #[test]
+    fn test_ntt_primes() {
+        let primes: Vec<NttPrime> = ntt_primes(30, 30, 23).unwrap().collect();
+        eprintln!("{:?}", primes);
+
+        assert!(primes.iter().any(|q| q.p == 998244353));
+        assert!(primes.iter().any(|q| q.p == 754974721));
+        assert!(primes.windows(2).all(|w| w[0].p < w[1].p));
+        for q in &primes {
+            assert_eq!(64 - q.p.leading_zeros(), 30);
+            assert!(q.k >= 23);
+            assert_eq!(NttPrime::new(q.p), Ok(*q));
+        }
+
+        // 愚直な列挙と一致する
+        let naive: Vec<u64> = (1 << 29..1 << 30)
+            .step_by(1 << 23)
+            .map(|p: u64| p + 1)
+            .filter(|&p| Fp::new(p).is_ok())
+            .collect();
+        assert_eq!(primes.iter().map(|q| q.p).collect::<Vec<_>>(), naive);
+    }
+
+    #[test]
+    fn test_ntt_primes_range() {
+        // 64 bit で 2^32 | p - 1 となる最大の素数は Goldilocks 素数
+        let largest = ntt_primes(64, 64, 32).unwrap().next_back().unwrap();
+        assert_eq!(largest, NTT_PRIMES[4]);
+
+        let small: Vec<u64> = ntt_primes(2, 5, 1).unwrap().map(|q| q.p).collect();
+        assert_eq!(small, vec![3, 5, 7, 11, 13, 17, 19, 23, 29, 31]);
+
+        assert_eq!(ntt_primes(10, 10, 64).unwrap().count(), 0);
+        assert!(ntt_primes(1, 10, 1).is_err());
+        assert!(ntt_primes(10, 9, 1).is_err());
+        assert!(ntt_primes(10, 65, 1).is_err());
+        assert!(ntt_primes(10, 20, 0).is_err());
+    }
+
+    #[test]
+    fn test_plan_moduli_single() {
+        assert_eq!(
+            plan_moduli(1 << 10, 100, 100),
+            Ok(ModulusPlan::Single(NttPrime::of::<167772161>()))
+        );
+        assert_eq!(
+            plan_moduli(1 << 20, 1000, 1000),
+            Ok(ModulusPlan::Single(NTT_PRIMES[4]))
+        );
+        assert_eq!(
+            plan_moduli(3, 2, 2),
+            Ok(ModulusPlan::Single(NttPrime::of::<7340033>()))
+        );
+
+        // 2^33 | p - 1 が必要なのでよく知られた素数は使えない
+        let plan = plan_moduli(1 << 33, 2, 2).unwrap();
+        assert!(matches!(plan, ModulusPlan::Single(q) if q.k >= 33));
+    }
+
+    #[test]
+    fn test_plan_moduli_crt() {
+        let plan = plan_moduli(1 << 20, 1 << 50, 1 << 50).unwrap();
+        let primes = plan.primes();
+        assert_eq!(primes.len(), 2);
+
+        let product = primes.iter().map(|q| q.p as u128).product::<u128>();
+        assert!(product > (1u128 << 20) * ((1 << 50) - 1) * ((1 << 50) - 1));
+        assert!(primes.iter().all(|q| q.k >= 20));
+
+        // 係数の上限が u64 に収まるが，条件を満たす最大の素数を超える場合
+        let max_coef = (1u128 << 33) * ((1 << 31) - 1);
+        assert!(max_coef < u64::MAX as u128);
+        let plan = plan_moduli(1 << 33, 1 << 31, 2).unwrap();
+        let primes = plan.primes();
+        assert_eq!(primes.len(), 2);
+        assert!(primes.iter().map(|q| q.p as u128).product::<u128>() > max_coef);
+        assert!(primes.iter().all(|q| q.k >= 33));
+
+        assert!(plan_moduli(0, 10, 10).is_err());
+        assert!(plan_moduli(1 << 20, 1 << 60, 1 << 60).is_err());
+    }
+
+    #[rstest(
+        n,
+        bound,
+        case(64, 1 << 20),
+        case(64, 1 << 40),
+        case(100, u64::MAX >> 4)
+    )]
+    fn test_plan_moduli_exact(n: usize, bound: u64) {
+        let mut rng = rng();
+
+        let a: Vec<u64> = (0..n).map(|_| rng.random_range(0..bound)).collect();
+        let b: Vec<u64> = (0..n).map(|_| rng.random_range(0..bound)).collect();
+
+        let mut expected = vec![0u128; 2 * n - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                expected[i + j] += x as u128 * y as u128;
+            }
+        }
+
+        let plan = plan_moduli(2 * n, bound, bound).unwrap();
+
+        let residues: Vec<Vec<u64>> = plan
+            .primes()
+            .iter()
+            .map(|q| {
+                let fft = FFT(q.to_fp());
+                let a: Vec<u64> = a.iter().map(|&v| v % q.p).collect();
+                let b: Vec<u64> = b.iter().map(|&v| v % q.p).collect();
+                fft.convolve(&a, &b).unwrap()
+            })
+            .collect();
+
+        let moduli: Vec<u64> = plan.primes().iter().map(|q| q.p).collect();
+        let garner = Garner::new(&moduli).unwrap();
+
+        for (i, &e) in expected.iter().enumerate() {
+            let r: Vec<u64> = residues.iter().map(|c| c[i]).collect();
+            assert_eq!(garner.reconstruct_u128(&r), Some(e));
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! 素数判定の実装
+
+use super::Montgomery;
+
+/// 試し割りに用いる小さな素数
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// 64 bit 整数に対する決定的 Miller–Rabin 法の底
+///
+/// n < 2^64 に対してはこれらの底で判定すれば十分である．
+const BASES: [u64; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
+
+/// n が素数であるか判定する（決定的 Miller–Rabin 法）
+///
+/// コンパイル時にも評価できるよう `const fn` として実装する．
+pub const fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut i = 0;
+    while i < SMALL_PRIMES.len() {
+        if n.is_multiple_of(SMALL_PRIMES[i]) {
+            return n == SMALL_PRIMES[i];
+        }
+        i += 1;
+    }
+
+    let mont = Montgomery::new(n);
+
+    let mut i = 0;
+    while i < BASES.len() {
+        if !miller_rabin(&mont, BASES[i]) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// 底 a について n が強擬素数であるか判定する
+const fn miller_rabin(mont: &Montgomery, a: u64) -> bool {
+    let n = mont.p;
+    let one = mont.one();
+    let minus_one = mont.neg(one);
+
+    let a = mont.to_mont(a);
+    if a == 0 {
+        return true;
+    }
+
+    // n - 1 = 2^s * d
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    let mut x = mont.pow(a, d);
+    if x == one || x == minus_one {
+        return true;
+    }
+    let mut i = 1;
+    while i < s {
+        x = mont.mul(x, x);
+        if x == minus_one {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::is_prime;
+
+    /// 試し割りによる素数判定
+    fn is_prime_naive(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn test_small() {
+        for n in 0..10000 {
+            assert_eq!(is_prime(n), is_prime_naive(n), "n = {n}");
+        }
+    }
+
+    #[rstest(
+        n,
+        expected,
+        case(998244353, true),
+        case(1000000007, true),
+        case(4294967291, true),
+        case(2305843009213693951, true), // 2^61 - 1
+        case(4179340454199820289, true), // 29 * 2^57 + 1
+        case(18446744069414584321, true), // 2^64 - 2^32 + 1
+        case(18446744073709551557, true), // 2^64 未満の最大の素数
+        case(561, false), // Carmichael 数
+        case(2047, false), // 底 2 の強擬素数
+        case(3215031751, false), // 底 2, 3, 5, 7 の強擬素数
+        case(3825123056546413051, false), // 底 2, ..., 23 の強擬素数
+        case(998244353 * 1000000007, false),
+        case(4294967291 * 4294967279, false),
+        case(u64::MAX, false),
+    )]
+    fn test_large(n: u64, expected: bool) {
+        assert_eq!(is_prime(n), expected);
+    }
+}
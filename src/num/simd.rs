@@ -0,0 +1,519 @@
+//! SIMD による Montgomery 演算の実装
+//!
+//! p < 2^32 の場合，Montgomery 表現の値は 64 bit のレーンの下位 32 bit に収まるため，
+//! 32 bit × 32 bit の乗算命令で REDC を計算できる．
+//! R = 2^64 の REDC は R' = 2^32 の REDC を 2 回行うことで求める．
+//! どの命令セットでも結果は [`Montgomery`] の演算と一致する．
+
+use super::Montgomery;
+
+/// 利用する命令セット
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Backend {
+    /// SIMD を用いない
+    Scalar,
+    /// AVX2（4 レーン）
+    Avx2,
+    /// AVX-512（8 レーン）
+    Avx512,
+}
+
+impl Backend {
+    /// 法 p の演算に利用できる最速の命令セットを実行時に選択する
+    pub(crate) fn select(mont: &Montgomery) -> Self {
+        if mont.p >> 32 != 0 {
+            return Backend::Scalar;
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx512f") {
+                return Backend::Avx512;
+            }
+            if is_x86_feature_detected!("avx2") {
+                return Backend::Avx2;
+            }
+        }
+        Backend::Scalar
+    }
+}
+
+/// SIMD を用いる配列の長さの下限
+///
+/// 短いブロックでは命令の切り替えの費用が上回るため，通常の演算で計算する．
+const SIMD_MIN_LEN: usize = 16;
+
+/// 法 p のバタフライ演算
+///
+/// 命令セットは作成時に 1 度だけ選択する．値はすべて Montgomery 表現で与える．
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Kernel {
+    /// Montgomery 乗算
+    mont: Montgomery,
+    /// 利用する命令セット
+    backend: Backend,
+}
+
+impl Kernel {
+    /// 実行環境で利用できる最速の命令セットを用いる
+    pub(crate) fn new(mont: Montgomery) -> Self {
+        Self::with_backend(mont, Backend::select(&mont))
+    }
+
+    /// 命令セットを指定する
+    ///
+    /// - `backend`: 実行環境で利用可能であり，p < 2^32 であること（`Scalar` を除く）
+    fn with_backend(mont: Montgomery, backend: Backend) -> Self {
+        Self { mont, backend }
+    }
+
+    /// 周波数間引きのバタフライ演算 (l_j, r_j) ← (l_j + r_j, (l_j - r_j) w_j) をまとめて行う
+    ///
+    /// - `pw`, `stride`: 回転因子 w_j = pw[j * stride]
+    #[inline]
+    pub(crate) fn dif(&self, L: &mut [u64], R: &mut [u64], pw: &[u64], stride: usize) {
+        debug_assert!(L.len() == R.len() && (L.is_empty() || (L.len() - 1) * stride < pw.len()));
+        if L.len() < SIMD_MIN_LEN {
+            return self.dif_scalar(L, R, pw, stride);
+        }
+        let mont = &self.mont;
+
+        let done = match self.backend {
+            Backend::Scalar => 0,
+            #[cfg(target_arch = "x86_64")]
+            // 作成時に命令セットが利用可能であることを確認済み
+            Backend::Avx2 => unsafe { avx2::dif_butterflies(mont, L, R, pw, stride) },
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx512 => unsafe { avx512::dif_butterflies(mont, L, R, pw, stride) },
+            #[cfg(not(target_arch = "x86_64"))]
+            _ => 0,
+        };
+
+        // SIMD で処理した残りを通常の演算で計算する
+        let pw = pw.get(done * stride..).unwrap_or(&[]);
+        self.dif_scalar(&mut L[done..], &mut R[done..], pw, stride);
+    }
+
+    /// 時間間引きのバタフライ演算 (l_j, r_j) ← (l_j + r_j w_j, l_j - r_j w_j) をまとめて行う
+    ///
+    /// - `pw`, `stride`: 回転因子 w_j = pw[j * stride]
+    #[inline]
+    pub(crate) fn dit(&self, L: &mut [u64], R: &mut [u64], pw: &[u64], stride: usize) {
+        debug_assert!(L.len() == R.len() && (L.is_empty() || (L.len() - 1) * stride < pw.len()));
+        if L.len() < SIMD_MIN_LEN {
+            return self.dit_scalar(L, R, pw, stride);
+        }
+        let mont = &self.mont;
+
+        let done = match self.backend {
+            Backend::Scalar => 0,
+            #[cfg(target_arch = "x86_64")]
+            // 作成時に命令セットが利用可能であることを確認済み
+            Backend::Avx2 => unsafe { avx2::dit_butterflies(mont, L, R, pw, stride) },
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx512 => unsafe { avx512::dit_butterflies(mont, L, R, pw, stride) },
+            #[cfg(not(target_arch = "x86_64"))]
+            _ => 0,
+        };
+
+        // SIMD で処理した残りを通常の演算で計算する
+        let pw = pw.get(done * stride..).unwrap_or(&[]);
+        self.dit_scalar(&mut L[done..], &mut R[done..], pw, stride);
+    }
+
+    /// [`Kernel::dif`] を通常の演算で計算する
+    #[inline]
+    fn dif_scalar(&self, L: &mut [u64], R: &mut [u64], pw: &[u64], stride: usize) {
+        let mont = &self.mont;
+        for ((l, r), &w) in L
+            .iter_mut()
+            .zip(R.iter_mut())
+            .zip(pw.iter().step_by(stride))
+        {
+            let (a, b) = (*l, *r);
+            *l = mont.add(a, b);
+            *r = mont.mul(mont.sub(a, b), w);
+        }
+    }
+
+    /// [`Kernel::dit`] を通常の演算で計算する
+    #[inline]
+    fn dit_scalar(&self, L: &mut [u64], R: &mut [u64], pw: &[u64], stride: usize) {
+        let mont = &self.mont;
+        for ((l, r), &w) in L
+            .iter_mut()
+            .zip(R.iter_mut())
+            .zip(pw.iter().step_by(stride))
+        {
+            let (a, b) = (*l, mont.mul(*r, w));
+            *l = mont.add(a, b);
+            *r = mont.sub(a, b);
+        }
+    }
+}
+
+/// AVX2 による実装
+///
+/// 各関数は AVX2 が利用可能な環境でのみ呼び出すこと．
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    use super::Montgomery;
+
+    /// 1 つのベクトルに格納する値の個数
+    const LANES: usize = 4;
+
+    /// a + b (mod p)
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn add(a: __m256i, b: __m256i, p: __m256i) -> __m256i {
+        let s = _mm256_add_epi64(a, b);
+        // s < 2^33 なので符号付き比較でよい
+        let lt = _mm256_cmpgt_epi64(p, s);
+        _mm256_sub_epi64(s, _mm256_andnot_si256(lt, p))
+    }
+
+    /// a - b (mod p)
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn sub(a: __m256i, b: __m256i, p: __m256i) -> __m256i {
+        let d = _mm256_sub_epi64(a, b);
+        let lt = _mm256_cmpgt_epi64(b, a);
+        _mm256_add_epi64(d, _mm256_and_si256(lt, p))
+    }
+
+    /// t * 2^(-32) (mod p)（0 <= t < p * 2^32）
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn reduce(t: __m256i, p: __m256i, pinv: __m256i) -> __m256i {
+        // t - mp は 2^32 で割り切れるため，上位 32 bit の差をとればよい
+        let m = _mm256_mul_epu32(t, pinv);
+        let mp = _mm256_mul_epu32(m, p);
+        sub(_mm256_srli_epi64::<32>(t), _mm256_srli_epi64::<32>(mp), p)
+    }
+
+    /// a * b * 2^(-64) (mod p)
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn mul(a: __m256i, b: __m256i, p: __m256i, pinv: __m256i) -> __m256i {
+        reduce(reduce(_mm256_mul_epu32(a, b), p, pinv), p, pinv)
+    }
+
+    /// 法と p^(-1) mod 2^32 を各レーンに格納したもの
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn consts(mont: &Montgomery) -> (__m256i, __m256i) {
+        (
+            _mm256_set1_epi64x(mont.p as i64),
+            _mm256_set1_epi64x(mont.pinv as u32 as i64),
+        )
+    }
+
+    /// 回転因子 pw[j * stride]（i <= j < i + LANES）を読み込む
+    ///
+    /// # Safety
+    /// AVX2 が利用可能であり，読み込む位置が pw の範囲内であること．
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn twiddles(pw: &[u64], i: usize, stride: usize) -> __m256i {
+        if stride == 1 {
+            return _mm256_loadu_si256(pw.as_ptr().add(i) as *const __m256i);
+        }
+        let s = stride as i64;
+        let idx = _mm256_add_epi64(
+            _mm256_set1_epi64x(i as i64 * s),
+            _mm256_set_epi64x(3 * s, 2 * s, s, 0),
+        );
+        _mm256_i64gather_epi64::<8>(pw.as_ptr() as *const i64, idx)
+    }
+
+    /// 周波数間引きのバタフライ演算
+    ///
+    /// **Returns**
+    /// - 計算した要素数（LANES の倍数）
+    ///
+    /// # Safety
+    /// AVX2 が利用可能であること．
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn dif_butterflies(
+        mont: &Montgomery,
+        L: &mut [u64],
+        R: &mut [u64],
+        pw: &[u64],
+        stride: usize,
+    ) -> usize {
+        let (p, pinv) = consts(mont);
+        let done = L.len() / LANES * LANES;
+
+        for i in (0..done).step_by(LANES) {
+            let l = L.as_mut_ptr().add(i) as *mut __m256i;
+            let r = R.as_mut_ptr().add(i) as *mut __m256i;
+            let w = twiddles(pw, i, stride);
+
+            let (a, b) = (_mm256_loadu_si256(l), _mm256_loadu_si256(r));
+            _mm256_storeu_si256(l, add(a, b, p));
+            _mm256_storeu_si256(r, mul(sub(a, b, p), w, p, pinv));
+        }
+        done
+    }
+
+    /// 時間間引きのバタフライ演算
+    ///
+    /// **Returns**
+    /// - 計算した要素数（LANES の倍数）
+    ///
+    /// # Safety
+    /// AVX2 が利用可能であること．
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn dit_butterflies(
+        mont: &Montgomery,
+        L: &mut [u64],
+        R: &mut [u64],
+        pw: &[u64],
+        stride: usize,
+    ) -> usize {
+        let (p, pinv) = consts(mont);
+        let done = L.len() / LANES * LANES;
+
+        for i in (0..done).step_by(LANES) {
+            let l = L.as_mut_ptr().add(i) as *mut __m256i;
+            let r = R.as_mut_ptr().add(i) as *mut __m256i;
+            let w = twiddles(pw, i, stride);
+
+            let a = _mm256_loadu_si256(l);
+            let b = mul(_mm256_loadu_si256(r), w, p, pinv);
+            _mm256_storeu_si256(l, add(a, b, p));
+            _mm256_storeu_si256(r, sub(a, b, p));
+        }
+        done
+    }
+}
+
+/// AVX-512 による実装
+///
+/// 各関数は AVX-512F が利用可能な環境でのみ呼び出すこと．
+#[cfg(target_arch = "x86_64")]
+mod avx512 {
+    use std::arch::x86_64::*;
+
+    use super::Montgomery;
+
+    /// 1 つのベクトルに格納する値の個数
+    const LANES: usize = 8;
+
+    /// a + b (mod p)
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    fn add(a: __m512i, b: __m512i, p: __m512i) -> __m512i {
+        let s = _mm512_add_epi64(a, b);
+        let ge = _mm512_cmpge_epu64_mask(s, p);
+        _mm512_mask_sub_epi64(s, ge, s, p)
+    }
+
+    /// a - b (mod p)
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    fn sub(a: __m512i, b: __m512i, p: __m512i) -> __m512i {
+        let d = _mm512_sub_epi64(a, b);
+        let lt = _mm512_cmplt_epu64_mask(a, b);
+        _mm512_mask_add_epi64(d, lt, d, p)
+    }
+
+    /// t * 2^(-32) (mod p)（0 <= t < p * 2^32）
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    fn reduce(t: __m512i, p: __m512i, pinv: __m512i) -> __m512i {
+        // t - mp は 2^32 で割り切れるため，上位 32 bit の差をとればよい
+        let m = _mm512_mul_epu32(t, pinv);
+        let mp = _mm512_mul_epu32(m, p);
+        sub(_mm512_srli_epi64::<32>(t), _mm512_srli_epi64::<32>(mp), p)
+    }
+
+    /// a * b * 2^(-64) (mod p)
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    fn mul(a: __m512i, b: __m512i, p: __m512i, pinv: __m512i) -> __m512i {
+        reduce(reduce(_mm512_mul_epu32(a, b), p, pinv), p, pinv)
+    }
+
+    /// 法と p^(-1) mod 2^32 を各レーンに格納したもの
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    fn consts(mont: &Montgomery) -> (__m512i, __m512i) {
+        (
+            _mm512_set1_epi64(mont.p as i64),
+            _mm512_set1_epi64(mont.pinv as u32 as i64),
+        )
+    }
+
+    /// 回転因子 pw[j * stride]（i <= j < i + LANES）を読み込む
+    ///
+    /// # Safety
+    /// AVX-512F が利用可能であり，読み込む位置が pw の範囲内であること．
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn twiddles(pw: &[u64], i: usize, stride: usize) -> __m512i {
+        if stride == 1 {
+            return _mm512_loadu_si512(pw.as_ptr().add(i) as *const __m512i);
+        }
+        let s = stride as i64;
+        let idx = _mm512_add_epi64(
+            _mm512_set1_epi64(i as i64 * s),
+            _mm512_set_epi64(7 * s, 6 * s, 5 * s, 4 * s, 3 * s, 2 * s, s, 0),
+        );
+        _mm512_i64gather_epi64::<8>(idx, pw.as_ptr() as *const i64)
+    }
+
+    /// 周波数間引きのバタフライ演算
+    ///
+    /// **Returns**
+    /// - 計算した要素数（LANES の倍数）
+    ///
+    /// # Safety
+    /// AVX-512F が利用可能であること．
+    #[target_feature(enable = "avx512f")]
+    pub(super) unsafe fn dif_butterflies(
+        mont: &Montgomery,
+        L: &mut [u64],
+        R: &mut [u64],
+        pw: &[u64],
+        stride: usize,
+    ) -> usize {
+        let (p, pinv) = consts(mont);
+        let done = L.len() / LANES * LANES;
+
+        for i in (0..done).step_by(LANES) {
+            let l = L.as_mut_ptr().add(i) as *mut __m512i;
+            let r = R.as_mut_ptr().add(i) as *mut __m512i;
+            let w = twiddles(pw, i, stride);
+
+            let (a, b) = (_mm512_loadu_si512(l), _mm512_loadu_si512(r));
+            _mm512_storeu_si512(l, add(a, b, p));
+            _mm512_storeu_si512(r, mul(sub(a, b, p), w, p, pinv));
+        }
+        done
+    }
+
+    /// 時間間引きのバタフライ演算
+    ///
+    /// **Returns**
+    /// - 計算した要素数（LANES の倍数）
+    ///
+    /// # Safety
+    /// AVX-512F が利用可能であること．
+    #[target_feature(enable = "avx512f")]
+    pub(super) unsafe fn dit_butterflies(
+        mont: &Montgomery,
+        L: &mut [u64],
+        R: &mut [u64],
+        pw: &[u64],
+        stride: usize,
+    ) -> usize {
+        let (p, pinv) = consts(mont);
+        let done = L.len() / LANES * LANES;
+
+        for i in (0..done).step_by(LANES) {
+            let l = L.as_mut_ptr().add(i) as *mut __m512i;
+            let r = R.as_mut_ptr().add(i) as *mut __m512i;
+            let w = twiddles(pw, i, stride);
+
+            let a = _mm512_loadu_si512(l);
+            let b = mul(_mm512_loadu_si512(r), w, p, pinv);
+            _mm512_storeu_si512(l, add(a, b, p));
+            _mm512_storeu_si512(r, sub(a, b, p));
+        }
+        done
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rand::{rng, Rng};
+    use rstest::rstest;
+
+    use super::{Backend, Kernel, Montgomery};
+
+    /// 実行環境で利用できる命令セット
+    fn backends() -> Vec<Backend> {
+        let mut res = vec![Backend::Scalar];
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                res.push(Backend::Avx2);
+            }
+            if is_x86_feature_detected!("avx512f") {
+                res.push(Backend::Avx512);
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn test_select() {
+        let big = Montgomery::new(18446744069414584321);
+        assert_eq!(Backend::select(&big), Backend::Scalar);
+
+        let small = Montgomery::new(998244353);
+        assert!(backends().contains(&Backend::select(&small)));
+    }
+
+    #[rstest(
+        p,
+        case(17),
+        case(998244353),
+        case(2013265921),
+        case(2147483647),
+        case(3221225473),
+        case(4293918721),
+        case(4294967291),
+        case(18446744069414584321)
+    )]
+    fn test_butterflies(p: u64) {
+        let mut rng = rng();
+        let mont = Montgomery::new(p);
+
+        for n in [0, 1, 3, 4, 7, 8, 9, 16, 17, 33, 64, 1000] {
+            for stride in [1, 2, 3] {
+                // 境界の値を含める
+                let mut gen = || match rng.random_range(0..4) {
+                    0 => 0,
+                    1 => p - 1,
+                    _ => rng.random_range(0..p),
+                };
+                let L: Vec<u64> = (0..n).map(|_| gen()).collect();
+                let R: Vec<u64> = (0..n).map(|_| gen()).collect();
+                // 回転因子の表は必要な長さちょうどにする
+                let len = if n == 0 { 0 } else { (n - 1) * stride + 1 };
+                let pw: Vec<u64> = (0..len).map(|_| gen()).collect();
+
+                // 通常の演算で計算した結果
+                let mut expected = (L.clone(), R.clone(), L.clone(), R.clone());
+                for j in 0..n {
+                    let (a, b, w) = (L[j], R[j], pw[j * stride]);
+                    expected.0[j] = mont.add(a, b);
+                    expected.1[j] = mont.mul(mont.sub(a, b), w);
+                    let b = mont.mul(b, w);
+                    expected.2[j] = mont.add(a, b);
+                    expected.3[j] = mont.sub(a, b);
+                }
+
+                for backend in backends() {
+                    if backend != Backend::Scalar && p >> 32 != 0 {
+                        continue;
+                    }
+                    let kernel = Kernel::with_backend(mont, backend);
+
+                    let (mut l, mut r) = (L.clone(), R.clone());
+                    kernel.dif(&mut l, &mut r, &pw, stride);
+                    assert_eq!((&l, &r), (&expected.0, &expected.1), "{backend:?}");
+
+                    let (mut l, mut r) = (L.clone(), R.clone());
+                    kernel.dit(&mut l, &mut r, &pw, stride);
+                    assert_eq!((&l, &r), (&expected.2, &expected.3), "{backend:?}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! コンパイル時に法を与える有限体の実装
+
+use super::{is_prime, Modulus, Montgomery};
+
+/// コンパイル時に法 `P` を与える有限体
+///
+/// 原始根などのパラメータはすべて `const fn` によりコンパイル時に計算される．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct StaticFp<const P: u64>;
+
+impl<const P: u64> StaticFp<P> {
+    /// mod p
+    pub const MOD: u64 = {
+        assert!(is_prime(P), "`P` should be prime number.");
+        assert!(P > 2, "`P` should be odd prime number.");
+        P
+    };
+    /// p の原始根
+    pub const ROOT: u64 = primitive_root(Self::MOD);
+    /// p = 2^k * m + 1 となるような k
+    pub const K: usize = (Self::MOD - 1).trailing_zeros() as usize;
+    /// p = 2^k * m + 1 となるような m
+    pub const M: u64 = (Self::MOD - 1) >> Self::K;
+    /// Montgomery 表現での演算
+    pub const MONT: Montgomery = Montgomery::new(Self::MOD);
+}
+
+impl<const P: u64> Modulus for StaticFp<P> {
+    #[inline]
+    fn modulus(&self) -> u64 {
+        Self::MOD
+    }
+
+    #[inline]
+    fn primitive_root(&self) -> u64 {
+        Self::ROOT
+    }
+
+    #[inline]
+    fn k(&self) -> usize {
+        Self::K
+    }
+
+    #[inline]
+    fn mont(&self) -> Montgomery {
+        Self::MONT
+    }
+
+    #[inline]
+    fn m(&self) -> u64 {
+        Self::M
+    }
+}
+
+/// 奇素数 p の最小の原始根を求める
+///
+/// p - 1 の素因数分解は試し割りで行うため，p - 1 が大きな素因数をもつ場合は
+/// コンパイル時の評価に時間がかかる．
+const fn primitive_root(p: u64) -> u64 {
+    // (p - 1) の相異なる素因数（u64 の相異なる素因数は高々 15 個）
+    let mut factors = [0; 15];
+    let mut len = 0;
+
+    let mut x = p - 1;
+    let mut d = 2;
+    while d <= x / d {
+        if x.is_multiple_of(d) {
+            factors[len] = d;
+            len += 1;
+            while x.is_multiple_of(d) {
+                x /= d;
+            }
+        }
+        d += if d == 2 { 1 } else { 2 };
+    }
+    if x > 1 {
+        factors[len] = x;
+        len += 1;
+    }
+
+    // 原始根を探索
+    let mont = Montgomery::new(p);
+    let one = mont.one();
+
+    let mut g = 2;
+    loop {
+        let mg = mont.to_mont(g);
+        let mut i = 0;
+        while i < len && mont.pow(mg, (p - 1) / factors[i]) != one {
+            i += 1;
+        }
+        if i == len {
+            return g;
+        }
+        g += 1;
+    }
+}
+
+// ===== テスト =====
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::num::{Fp, Modulus};
+
+    use super::StaticFp;
+
+    /// コンパイル時に評価できる
+    const ROOT_998244353: u64 = StaticFp::<998244353>::ROOT;
+
+    #[test]
+    fn test_const() {
+        assert_eq!(ROOT_998244353, 3);
+        assert_eq!(StaticFp::<998244353>::K, 23);
+        assert_eq!(StaticFp::<998244353>::M, 119);
+        assert_eq!(StaticFp::<5>::ROOT, 2);
+        assert_eq!(StaticFp::<7>::ROOT, 3);
+        assert_eq!(StaticFp::<11>::ROOT, 2);
+    }
+
+    fn check<const P: u64>() {
+        let fp = Fp::new(P).unwrap();
+        let sfp = StaticFp::<P>;
+
+        assert_eq!(sfp.modulus(), fp.modulus());
+        assert_eq!(sfp.primitive_root(), fp.root);
+        assert_eq!(sfp.k(), fp.k);
+        assert_eq!(sfp.m(), fp.m);
+        assert_eq!(sfp.mont(), fp.mont);
+
+        for a in 0..=sfp.k() {
+            assert_eq!(sfp.root_pow2m(a), fp.root_pow2m(a));
+        }
+        assert!(sfp.root_pow2m(sfp.k() + 1).is_err());
+    }
+
+    #[rstest(
+        f,
+        case(check::<5>),
+        case(check::<17>),
+        case(check::<12289>),
+        case(check::<5767169>),
+        case(check::<7340033>),
+        case(check::<167772161>),
+        case(check::<469762049>),
+        case(check::<998244353>),
+        case(check::<4179340454199820289>),
+        case(check::<18446744069414584321>)
+    )]
+    fn test_same_as_fp(f: fn()) {
+        f();
+    }
+}